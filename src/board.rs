@@ -1,14 +1,19 @@
+use crate::evaluate;
+use crate::magic;
 use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
 use std::time::{Duration, Instant};
 
+// 評価モジュール（`crate::evaluate`）から盤面を読めるよう、駒の型は crate 内に公開する
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
-enum Color {
+pub(crate) enum Color {
     White,
     Black,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
-enum Kind {
+pub(crate) enum Kind {
     Pawn,
     Knight,
     Bishop,
@@ -18,9 +23,9 @@ enum Kind {
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
-struct Piece {
-    kind: Kind,
-    color: Color,
+pub(crate) struct Piece {
+    pub(crate) kind: Kind,
+    pub(crate) color: Color,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -34,17 +39,327 @@ pub struct Move {
     is_castle_queenside: bool,
 }
 
+/// `Board::make_move` が返す巻き戻し情報
+///
+/// 盤面を丸ごとクローンする代わりに、`make_move` → (探索) → `unmake_move` で
+/// 同一の盤面を使い回せるようにするために、`make_move` が上書きする状態を
+/// すべて記録しておく
+struct UndoInfo {
+    /// 移動した駒（昇格前の元の駒。昇格手の巻き戻しではこれをポーンとして戻す）
+    moved_piece: Piece,
+    /// 捕獲された駒。通常の捕獲は`to`に、アンパッサンは`ep_capture_square`にいた駒
+    captured: Option<Piece>,
+    /// アンパッサンで捕獲されたポーンの実際のマス（`to`とは異なるマス）
+    ep_capture_square: Option<usize>,
+    old_castle_wk: bool,
+    old_castle_wq: bool,
+    old_castle_bk: bool,
+    old_castle_bq: bool,
+    old_ep_square: Option<usize>,
+    old_halfmove_clock: u32,
+    old_fullmove_number: u32,
+    old_zobrist: u64,
+    /// この手で`history`がクリアされたかどうか。trueなら`old_history`をそのまま
+    /// 復元し、falseなら今回pushした1件をpopするだけでよい
+    history_was_reset: bool,
+    old_history: Vec<u64>,
+}
+
 #[derive(Clone)]
 pub struct Board {
     sq: [Option<Piece>; 64],
+    // `sq` と常に同期を保つビットボード表現。`attacked_by` による利き判定を
+    // 1マスずつの全駒スキャンではなくビット演算にするためのもので、`set_piece`
+    // の中でのみ更新される（外部からは従来どおり `piece_at`/`set_piece` 経由で触る）
+    bb: [[u64; 6]; 2],    // [color][kind]
+    occ_by_color: [u64; 2], // [color] その色の駒がいるマスの合併
     side: Color,
     castle_wk: bool,
     castle_wq: bool,
     castle_bk: bool,
     castle_bq: bool,
+    // キャスリングに関わるキング・ルークの初期ファイル。標準チェスでは常に
+    // king=4, rook_k=7, rook_q=0 だが、Chess960では開始局面ごとに異なるため、
+    // キャスリングの判定・適用をこの3つのファイル番号だけに依存させて一般化している
+    king_start_file: usize,
+    rook_start_file_k: usize,
+    rook_start_file_q: usize,
     ep_square: Option<usize>, // アンパッサン可能な取り先
     halfmove_clock: u32,
     fullmove_number: u32,
+    zobrist: u64, // 現局面のZobristハッシュ（差分更新される）
+    // 直前の不可逆手（ポーンの移動・駒の捕獲）以降に現れたZobristハッシュの履歴。
+    // 同一局面が3回出現したら千日手なので、`is_threefold_repetition` で数える
+    history: Vec<u64>,
+}
+
+/// Zobristハッシュ用の乱数テーブル
+///
+/// 駒種×色×マス（12×64）、手番、キャスリング権4種、アンパッサンのファイル8種を保持する
+struct ZobristTable {
+    pieces: [[[u64; 64]; 6]; 2], // [color][kind][square]
+    side: u64,
+    castling: [u64; 4], // wk, wq, bk, bq
+    ep_file: [u64; 8],
+}
+
+/// SplitMix64: 固定シードから再現可能な乱数列を生成する簡易PRNG
+///
+/// 外部crateに頼らず、起動のたびに同じZobrist定数を得るために使う
+pub(crate) struct SplitMix64(u64);
+
+impl SplitMix64 {
+    /// 指定したシードから新しいSplitMix64乱数生成器を作る
+    pub(crate) fn new(seed: u64) -> Self {
+        SplitMix64(seed)
+    }
+
+    pub(crate) fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+}
+
+/// Zobristテーブルを取得する（初回呼び出し時に一度だけ初期化）
+fn zobrist_table() -> &'static ZobristTable {
+    static TABLE: OnceLock<ZobristTable> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let mut rng = SplitMix64(0x243F6A8885A308D3);
+        let mut pieces = [[[0u64; 64]; 6]; 2];
+        for color in pieces.iter_mut() {
+            for kind in color.iter_mut() {
+                for sq in kind.iter_mut() {
+                    *sq = rng.next();
+                }
+            }
+        }
+        ZobristTable {
+            pieces,
+            side: rng.next(),
+            castling: [rng.next(), rng.next(), rng.next(), rng.next()],
+            ep_file: std::array::from_fn(|_| rng.next()),
+        }
+    })
+}
+
+fn kind_index(kind: Kind) -> usize {
+    match kind {
+        Kind::Pawn => 0,
+        Kind::Knight => 1,
+        Kind::Bishop => 2,
+        Kind::Rook => 3,
+        Kind::Queen => 4,
+        Kind::King => 5,
+    }
+}
+
+fn color_index(color: Color) -> usize {
+    match color {
+        Color::White => 0,
+        Color::Black => 1,
+    }
+}
+
+/// 指定の駒がそのマスにあるときのZobrist定数を取得する
+fn piece_key(color: Color, kind: Kind, square: usize) -> u64 {
+    zobrist_table().pieces[color_index(color)][kind_index(kind)][square]
+}
+
+/// キャスリング権（0=wk, 1=wq, 2=bk, 3=bq）のZobrist定数を取得する
+fn castle_key(right: usize) -> u64 {
+    zobrist_table().castling[right]
+}
+
+/// アンパッサンのファイルに対応するZobrist定数を取得する
+fn ep_key(file: usize) -> u64 {
+    zobrist_table().ep_file[file]
+}
+
+/// 手番のZobrist定数を取得する
+fn side_key() -> u64 {
+    zobrist_table().side
+}
+
+/// アンパッサンのファイルがハッシュに影響するのは、実際にそのアンパッサンを
+/// 捕獲できる相手ポーンが盤上にある場合だけ。`ep_square`が立っているだけでは
+/// 局面を区別する要素にならない（どの道誰も使えない権利なので）ので、
+/// ここで捕獲可能性を確認してからハッシュ対象のファイルを返す
+///
+/// # 引数
+/// * `sq` - 駒の配置
+/// * `capturing_side` - アンパッサンを捕獲しうる側（ep_squareが立った直後の手番）
+/// * `ep_square` - アンパッサンの対象マス（通過された中間マス）
+fn ep_hash_file(sq: &[Option<Piece>; 64], capturing_side: Color, ep_square: Option<usize>) -> Option<usize> {
+    let ep = ep_square?;
+    let file = file_of(ep) as isize;
+    let rank = rank_of(ep) as isize;
+    // 捕獲する側のポーンは、epスクエアと同じランクではなく、取られるポーンが実際にいる
+    // ランク（epスクエアから見て捕獲側に一段近い側）にいる
+    let pawn_rank = if capturing_side == Color::White { rank - 1 } else { rank + 1 };
+    if !(0..8).contains(&pawn_rank) {
+        return None;
+    }
+    for df in [-1isize, 1] {
+        let f = file + df;
+        if (0..8).contains(&f) {
+            if let Some(p) = sq[to_idx(f, pawn_rank)] {
+                if p.kind == Kind::Pawn && p.color == capturing_side {
+                    return Some(file_of(ep));
+                }
+            }
+        }
+    }
+    None
+}
+
+/// ナイト・キングの利きテーブル（マスごとに事前計算した移動先ビットボード）
+struct LeaperAttackTables {
+    knight: [u64; 64],
+    king: [u64; 64],
+}
+
+/// ナイト・キングの利きテーブルを取得する（初回呼び出し時に一度だけ初期化）
+///
+/// 毎回ループで移動先を数え上げるのではなく、起動時に1回だけ計算してビットボードで持つ
+fn leaper_attack_tables() -> &'static LeaperAttackTables {
+    static TABLE: OnceLock<LeaperAttackTables> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let mut knight = [0u64; 64];
+        let mut king = [0u64; 64];
+        const KNIGHT_DELTAS: [(isize, isize); 8] = [
+            (1, 2), (2, 1), (2, -1), (1, -2),
+            (-1, -2), (-2, -1), (-2, 1), (-1, 2),
+        ];
+        const KING_DELTAS: [(isize, isize); 8] = [
+            (1, 0), (1, 1), (0, 1), (-1, 1),
+            (-1, 0), (-1, -1), (0, -1), (1, -1),
+        ];
+        for sq in 0..64 {
+            let f = file_of(sq) as isize;
+            let r = rank_of(sq) as isize;
+            for &(df, dr) in KNIGHT_DELTAS.iter() {
+                let (nf, nr) = (f + df, r + dr);
+                if in_bounds(nf, nr) {
+                    knight[sq] |= 1u64 << to_idx(nf, nr);
+                }
+            }
+            for &(df, dr) in KING_DELTAS.iter() {
+                let (nf, nr) = (f + df, r + dr);
+                if in_bounds(nf, nr) {
+                    king[sq] |= 1u64 << to_idx(nf, nr);
+                }
+            }
+        }
+        LeaperAttackTables { knight, king }
+    })
+}
+
+/// 1マスずつレイを伸ばして、盤外もしくは駒にぶつかるまでのスライディング利きを計算する
+///
+/// ビショップ/ルーク/クイーンの利き計算に使う。マジックビットボード導入前の素朴な実装
+pub(crate) fn sliding_attacks(sq: usize, occupied: u64, deltas: &[(isize, isize)]) -> u64 {
+    let mut attacks = 0u64;
+    let f = file_of(sq) as isize;
+    let r = rank_of(sq) as isize;
+    for &(df, dr) in deltas {
+        let (mut nf, mut nr) = (f + df, r + dr);
+        while in_bounds(nf, nr) {
+            let dest = to_idx(nf, nr);
+            attacks |= 1u64 << dest;
+            if occupied & (1u64 << dest) != 0 {
+                break;
+            }
+            nf += df;
+            nr += dr;
+        }
+    }
+    attacks
+}
+
+/// Chess960の開始局面番号（0..=959）からバックランク（8マス分の駒種）を求める
+///
+/// 標準的な割り当て方式: ビショップを異色マスに1つずつ、残りの空きマスから
+/// クイーン・ナイト2体を順に確定させ、最後に残った3マスに左からルーク・キング・ルークを置く
+/// （キングは必ず2つのルークに挟まれる）
+fn chess960_back_rank(id: u16) -> [Kind; 8] {
+    let mut n = id as usize;
+    let mut squares: [Option<Kind>; 8] = [None; 8];
+
+    // 明マス（奇数インデックス: b/d/f/h）にビショップを1つ
+    let bishop_light = n % 4;
+    n /= 4;
+    squares[bishop_light * 2 + 1] = Some(Kind::Bishop);
+
+    // 暗マス（偶数インデックス: a/c/e/g）にもう1つのビショップ
+    let bishop_dark = n % 4;
+    n /= 4;
+    squares[bishop_dark * 2] = Some(Kind::Bishop);
+
+    // 残り6マスのうち n%6 番目にクイーン
+    let q = n % 6;
+    n /= 6;
+    let empty: Vec<usize> = (0..8).filter(|&i| squares[i].is_none()).collect();
+    squares[empty[q]] = Some(Kind::Queen);
+
+    // 残り5マスから2マスを選んでナイトを置く（組み合わせは10通り）
+    const KNIGHT_TABLE: [(usize, usize); 10] = [
+        (0, 1), (0, 2), (0, 3), (0, 4),
+        (1, 2), (1, 3), (1, 4),
+        (2, 3), (2, 4),
+        (3, 4),
+    ];
+    let (k1, k2) = KNIGHT_TABLE[n];
+    let empty: Vec<usize> = (0..8).filter(|&i| squares[i].is_none()).collect();
+    squares[empty[k1]] = Some(Kind::Knight);
+    squares[empty[k2]] = Some(Kind::Knight);
+
+    // 残った3マスに左からルーク・キング・ルーク
+    let empty: Vec<usize> = (0..8).filter(|&i| squares[i].is_none()).collect();
+    squares[empty[0]] = Some(Kind::Rook);
+    squares[empty[1]] = Some(Kind::King);
+    squares[empty[2]] = Some(Kind::Rook);
+
+    std::array::from_fn(|i| squares[i].unwrap())
+}
+
+/// 盤面全体からZobristハッシュをゼロから計算する（初期化専用）
+fn compute_zobrist(
+    sq: &[Option<Piece>; 64],
+    side: Color,
+    castle_wk: bool,
+    castle_wq: bool,
+    castle_bk: bool,
+    castle_bq: bool,
+    ep_square: Option<usize>,
+) -> u64 {
+    let mut h = 0u64;
+    for (i, cell) in sq.iter().enumerate() {
+        if let Some(p) = cell {
+            h ^= piece_key(p.color, p.kind, i);
+        }
+    }
+    if side == Color::Black {
+        h ^= side_key();
+    }
+    if castle_wk {
+        h ^= castle_key(0);
+    }
+    if castle_wq {
+        h ^= castle_key(1);
+    }
+    if castle_bk {
+        h ^= castle_key(2);
+    }
+    if castle_bq {
+        h ^= castle_key(3);
+    }
+    if let Some(file) = ep_hash_file(sq, side, ep_square) {
+        h ^= ep_key(file);
+    }
+    h
 }
 
 /// ファイルとランクから盤面インデックス（0..63）を計算する
@@ -88,6 +403,73 @@ fn to_idx(file: isize, rank: isize) -> usize {
     (rank as usize) * 8 + (file as usize)
 }
 
+/// 置換表のエントリ種別（探索を途中で打ち切った際にどちら向きの境界かを示す）
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum TTFlag {
+    Exact,
+    LowerBound,
+    UpperBound,
+}
+
+#[derive(Clone, Copy)]
+struct TTEntry {
+    key: u64,
+    depth: u32,
+    value: i32,
+    flag: TTFlag,
+    best_move: Option<Move>,
+}
+
+/// 置換表のエントリ数（Zobristキーを剰余でインデックスに変換する）
+const TT_SIZE: usize = 1 << 16;
+
+/// 置換表: 探索済み局面の評価値と最善手を再利用するためのハッシュテーブル
+struct TranspositionTable {
+    entries: Vec<Option<TTEntry>>,
+}
+
+impl TranspositionTable {
+    /// 固定サイズの置換表を確保する
+    fn new() -> Self {
+        TranspositionTable {
+            entries: vec![None; TT_SIZE],
+        }
+    }
+
+    fn index(&self, key: u64) -> usize {
+        (key as usize) % self.entries.len()
+    }
+
+    /// キーに対応するエントリを取得する（インデックス衝突はキー完全一致で検出）
+    ///
+    /// `TTEntry` は `Copy` なので値で返す。スレッド間で共有する`Mutex`越しに
+    /// 呼び出した際、ロックを長く保持せずに済む
+    fn probe(&self, key: u64) -> Option<TTEntry> {
+        let i = self.index(key);
+        self.entries[i].filter(|e| e.key == key)
+    }
+
+    /// 深さ優先で置換表にエントリを格納する
+    ///
+    /// 同一インデックスに既存エントリがある場合、既存エントリと同じかより深い探索の結果のみ上書きする
+    fn store(&mut self, key: u64, depth: u32, value: i32, flag: TTFlag, best_move: Option<Move>) {
+        let i = self.index(key);
+        let replace = match &self.entries[i] {
+            Some(existing) => existing.depth <= depth,
+            None => true,
+        };
+        if replace {
+            self.entries[i] = Some(TTEntry {
+                key,
+                depth,
+                value,
+                flag,
+                best_move,
+            });
+        }
+    }
+}
+
 impl Board {
     /// チェスの初期配置で盤面を作成する
     pub fn new() -> Self {
@@ -120,35 +502,459 @@ impl Board {
             });
         }
 
+        let zobrist = compute_zobrist(&sq, White, true, true, true, true, None);
+        let (bb, occ_by_color) = Board::bitboards_from_sq(&sq);
+
         Board {
             sq,
+            bb,
+            occ_by_color,
             side: White,
             castle_wk: true,
             castle_wq: true,
             castle_bk: true,
             castle_bq: true,
+            king_start_file: 4,
+            rook_start_file_k: 7,
+            rook_start_file_q: 0,
             ep_square: None,
             halfmove_clock: 0,
             fullmove_number: 1,
+            zobrist,
+            history: vec![zobrist],
+        }
+    }
+
+    /// FEN文字列から盤面を構築する
+    ///
+    /// 駒配置・手番・キャスリング権・アンパッサン・半手数・手数カウンタのすべてを読み取る。
+    /// `position fen <FEN>` でエンジンを駆動できるようにするためのローダー。
+    /// 各ランクがちょうど8マス分かどうか、各色のキングがちょうど1枚かどうかを検証し、
+    /// 不正な局面はエラーとして拒否する
+    ///
+    /// キャスリングフィールドは標準のK/Q/k/q表記に加え、Shredder-FEN（Chess960）の
+    /// ルークファイル文字表記（白は大文字A-H、黒は小文字a-h）にも対応する。後者の場合、
+    /// 盤上のキングの位置からキャスリングの開始ファイルを読み取る
+    ///
+    /// # 引数
+    /// * `fen` - FEN形式の文字列（空白区切りで6フィールド: 配置 手番 キャスリング ep 半手数 手数）。
+    ///   末尾の半手数・手数は省略可能（省略時はそれぞれ0, 1とみなす）
+    pub fn from_fen(fen: &str) -> Result<Board, String> {
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        if fields.len() < 4 {
+            return Err(format!("Bad FEN '{}': expected at least 4 fields", fen));
+        }
+
+        let mut sq = [None; 64];
+        let ranks: Vec<&str> = fields[0].split('/').collect();
+        if ranks.len() != 8 {
+            return Err(format!("Bad FEN '{}': expected 8 ranks", fen));
+        }
+        for (r, rank_str) in ranks.iter().enumerate() {
+            let rank = 7 - r; // FENは8段目(黒側)から順に並ぶ
+            let mut file = 0usize;
+            for c in rank_str.chars() {
+                if let Some(skip) = c.to_digit(10) {
+                    file += skip as usize;
+                    continue;
+                }
+                if file >= 8 {
+                    return Err(format!("Bad FEN '{}': rank '{}' overflows", fen, rank_str));
+                }
+                let color = if c.is_ascii_uppercase() {
+                    Color::White
+                } else {
+                    Color::Black
+                };
+                let kind = match c.to_ascii_lowercase() {
+                    'p' => Kind::Pawn,
+                    'n' => Kind::Knight,
+                    'b' => Kind::Bishop,
+                    'r' => Kind::Rook,
+                    'q' => Kind::Queen,
+                    'k' => Kind::King,
+                    _ => return Err(format!("Bad FEN '{}': unknown piece '{}'", fen, c)),
+                };
+                sq[idx(file, rank)] = Some(Piece { kind, color });
+                file += 1;
+            }
+            if file != 8 {
+                return Err(format!("Bad FEN '{}': rank '{}' has {} files, expected 8", fen, rank_str, file));
+            }
+        }
+
+        let white_kings = sq.iter().filter(|p| matches!(p, Some(Piece { kind: Kind::King, color: Color::White }))).count();
+        let black_kings = sq.iter().filter(|p| matches!(p, Some(Piece { kind: Kind::King, color: Color::Black }))).count();
+        if white_kings != 1 || black_kings != 1 {
+            return Err(format!(
+                "Bad FEN '{}': expected exactly 1 king per side, found {} white and {} black",
+                fen, white_kings, black_kings
+            ));
+        }
+
+        let side = match fields[1] {
+            "w" => Color::White,
+            "b" => Color::Black,
+            other => return Err(format!("Bad FEN '{}': unknown side to move '{}'", fen, other)),
+        };
+
+        let castling = fields[2];
+        // Shredder-FEN（Chess960）はキャスリング権をK/Q/k/qではなく、キャスリングする
+        // ルークの開始ファイルを文字（白は大文字A-H、黒は小文字a-h）で表す。K/Qはどちらの
+        // 表記にも現れず(a-hの範囲外)、衝突なく判別できる
+        let is_shredder = castling
+            .chars()
+            .any(|c| ('A'..='H').contains(&c) || ('a'..='h').contains(&c));
+
+        let (castle_wk, castle_wq, castle_bk, castle_bq, king_start_file, rook_start_file_k, rook_start_file_q) =
+            if is_shredder {
+                let white_king_file = (0..8)
+                    .find(|&f| matches!(sq[idx(f, 0)], Some(Piece { kind: Kind::King, color: Color::White })))
+                    .ok_or_else(|| format!("Bad FEN '{}': Shredder castling needs a white king on rank 1", fen))?;
+                let black_king_file = (0..8)
+                    .find(|&f| matches!(sq[idx(f, 7)], Some(Piece { kind: Kind::King, color: Color::Black })))
+                    .ok_or_else(|| format!("Bad FEN '{}': Shredder castling needs a black king on rank 8", fen))?;
+                // `king_start_file`/`rook_start_file_*`は白黒で共有の1組しか持たないので
+                // （Chess960の開始局面は左右対称という前提）、両キングが異なるファイルにいる
+                // 局面はこの盤面表現では表せない
+                if white_king_file != black_king_file {
+                    return Err(format!(
+                        "Bad FEN '{}': Shredder castling requires both kings on the same file, found white={} black={}",
+                        fen, white_king_file, black_king_file
+                    ));
+                }
+
+                let mut castle_wk = false;
+                let mut castle_wq = false;
+                let mut castle_bk = false;
+                let mut castle_bq = false;
+                // 白黒どちらの表記で読んだファイルでも同じ`rook_start_file_k/q`に反映する
+                // （どちらか一方の色しかキャスリング権を保持していなくても開始ファイルを失わない）
+                let mut rook_start_file_k: Option<usize> = None;
+                let mut rook_start_file_q: Option<usize> = None;
+
+                for c in castling.chars() {
+                    if ('A'..='H').contains(&c) {
+                        let file = (c as u8 - b'A') as usize;
+                        if file > white_king_file {
+                            rook_start_file_k = Some(file);
+                            castle_wk = true;
+                        } else {
+                            rook_start_file_q = Some(file);
+                            castle_wq = true;
+                        }
+                    } else if ('a'..='h').contains(&c) {
+                        let file = (c as u8 - b'a') as usize;
+                        if file > black_king_file {
+                            rook_start_file_k = Some(file);
+                            castle_bk = true;
+                        } else {
+                            rook_start_file_q = Some(file);
+                            castle_bq = true;
+                        }
+                    } else if c != '-' {
+                        return Err(format!("Bad FEN '{}': unexpected Shredder castling symbol '{}'", fen, c));
+                    }
+                }
+
+                (
+                    castle_wk,
+                    castle_wq,
+                    castle_bk,
+                    castle_bq,
+                    white_king_file,
+                    rook_start_file_k.unwrap_or(7),
+                    rook_start_file_q.unwrap_or(0),
+                )
+            } else {
+                (
+                    castling.contains('K'),
+                    castling.contains('Q'),
+                    castling.contains('k'),
+                    castling.contains('q'),
+                    4,
+                    7,
+                    0,
+                )
+            };
+
+        let ep_square = match fields[3] {
+            "-" => None,
+            s => Some(parse_square(s)?),
+        };
+
+        let halfmove_clock = match fields.get(4) {
+            Some(s) => s.parse().map_err(|_| format!("Bad FEN '{}': invalid halfmove clock '{}'", fen, s))?,
+            None => 0,
+        };
+        let fullmove_number = match fields.get(5) {
+            Some(s) => s.parse().map_err(|_| format!("Bad FEN '{}': invalid fullmove number '{}'", fen, s))?,
+            None => 1,
+        };
+
+        let zobrist = compute_zobrist(&sq, side, castle_wk, castle_wq, castle_bk, castle_bq, ep_square);
+        let (bb, occ_by_color) = Board::bitboards_from_sq(&sq);
+
+        Ok(Board {
+            sq,
+            bb,
+            occ_by_color,
+            side,
+            castle_wk,
+            castle_wq,
+            castle_bk,
+            castle_bq,
+            // Shredder-FEN（ルークのファイル文字によるキャスリング表記）ならそこから
+            // 開始ファイルを読み取り、標準のK/Q/k/q表記なら標準配置（e/h/a）を仮定する
+            king_start_file,
+            rook_start_file_k,
+            rook_start_file_q,
+            ep_square,
+            halfmove_clock,
+            fullmove_number,
+            zobrist,
+            history: vec![zobrist],
+        })
+    }
+
+    /// Chess960（フィッシャーランダムチェス）の開始局面をIDから構築する
+    ///
+    /// `id` は0〜959の960通りの初期配置に対応する標準的な割り当て（Wikipediaの
+    /// "Chess960 numbering scheme"）に従う。白黒とも同じバックランクを使い、
+    /// ポーンは通常どおり2段目・7段目に並べる。両者のキャスリング権は全て有効とする
+    ///
+    /// # 引数
+    /// * `id` - 0..=959 の開始局面番号
+    pub fn from_chess960_id(id: u16) -> Board {
+        let back_rank = chess960_back_rank(id % 960);
+        let king_start_file = back_rank.iter().position(|&k| k == Kind::King).unwrap();
+        let rook_files: Vec<usize> = back_rank
+            .iter()
+            .enumerate()
+            .filter(|&(_, &k)| k == Kind::Rook)
+            .map(|(f, _)| f)
+            .collect();
+        let rook_start_file_q = rook_files[0];
+        let rook_start_file_k = rook_files[1];
+
+        let mut sq = [None; 64];
+        for (f, &kind) in back_rank.iter().enumerate() {
+            sq[idx(f, 0)] = Some(Piece { kind, color: Color::White });
+            sq[idx(f, 1)] = Some(Piece { kind: Kind::Pawn, color: Color::White });
+            sq[idx(f, 7)] = Some(Piece { kind, color: Color::Black });
+            sq[idx(f, 6)] = Some(Piece { kind: Kind::Pawn, color: Color::Black });
+        }
+
+        let zobrist = compute_zobrist(&sq, Color::White, true, true, true, true, None);
+        let (bb, occ_by_color) = Board::bitboards_from_sq(&sq);
+
+        Board {
+            sq,
+            bb,
+            occ_by_color,
+            side: Color::White,
+            castle_wk: true,
+            castle_wq: true,
+            castle_bk: true,
+            castle_bq: true,
+            king_start_file,
+            rook_start_file_k,
+            rook_start_file_q,
+            ep_square: None,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            zobrist,
+            history: vec![zobrist],
+        }
+    }
+
+    /// 盤面をFEN文字列に変換する
+    ///
+    /// `from_fen` の逆変換で、駒配置・手番・キャスリング権・アンパッサン・
+    /// 半手数・手数カウンタを空白区切り6フィールドの標準形式で出力する。
+    /// キャスリング権は常にK/Q/k/q表記で出力し、Shredder-FENのルークファイル文字表記は
+    /// 出力しない（標準配置以外のキング・ルーク開始ファイルはこの出力には残らない）
+    pub fn to_fen(&self) -> String {
+        let mut placement = String::new();
+        for rank in (0..8).rev() {
+            let mut empty_run = 0u32;
+            for file in 0..8 {
+                match self.sq[idx(file, rank)] {
+                    None => empty_run += 1,
+                    Some(p) => {
+                        if empty_run > 0 {
+                            placement.push_str(&empty_run.to_string());
+                            empty_run = 0;
+                        }
+                        let c = match (p.kind, p.color) {
+                            (Kind::Pawn, Color::White) => 'P',
+                            (Kind::Knight, Color::White) => 'N',
+                            (Kind::Bishop, Color::White) => 'B',
+                            (Kind::Rook, Color::White) => 'R',
+                            (Kind::Queen, Color::White) => 'Q',
+                            (Kind::King, Color::White) => 'K',
+                            (Kind::Pawn, Color::Black) => 'p',
+                            (Kind::Knight, Color::Black) => 'n',
+                            (Kind::Bishop, Color::Black) => 'b',
+                            (Kind::Rook, Color::Black) => 'r',
+                            (Kind::Queen, Color::Black) => 'q',
+                            (Kind::King, Color::Black) => 'k',
+                        };
+                        placement.push(c);
+                    }
+                }
+            }
+            if empty_run > 0 {
+                placement.push_str(&empty_run.to_string());
+            }
+            if rank > 0 {
+                placement.push('/');
+            }
+        }
+
+        let side = if self.side == Color::White { "w" } else { "b" };
+
+        let mut castling = String::new();
+        if self.castle_wk {
+            castling.push('K');
+        }
+        if self.castle_wq {
+            castling.push('Q');
+        }
+        if self.castle_bk {
+            castling.push('k');
         }
+        if self.castle_bq {
+            castling.push('q');
+        }
+        if castling.is_empty() {
+            castling.push('-');
+        }
+
+        let ep = match self.ep_square {
+            Some(sq) => {
+                let f = (b'a' + file_of(sq) as u8) as char;
+                let r = (b'1' + rank_of(sq) as u8) as char;
+                format!("{}{}", f, r)
+            }
+            None => "-".to_string(),
+        };
+
+        format!(
+            "{} {} {} {} {} {}",
+            placement, side, castling, ep, self.halfmove_clock, self.fullmove_number
+        )
+    }
+
+    /// 現局面のZobristハッシュを取得する
+    ///
+    /// `make_move` の中で差分更新されるため、呼び出しコストはO(1)
+    pub fn zobrist(&self) -> u64 {
+        self.zobrist
     }
 
     /// 指定された位置の駒を取得する
     ///
     /// # 引数
     /// * `i` - 盤面インデックス (0..63)
-    fn piece_at(&self, i: usize) -> Option<Piece> {
+    pub(crate) fn piece_at(&self, i: usize) -> Option<Piece> {
         self.sq[i]
     }
+
+    /// 白のキングサイドキャスリング権が残っているか
+    pub(crate) fn castle_wk(&self) -> bool {
+        self.castle_wk
+    }
+    /// 白のクイーンサイドキャスリング権が残っているか
+    pub(crate) fn castle_wq(&self) -> bool {
+        self.castle_wq
+    }
+    /// 黒のキングサイドキャスリング権が残っているか
+    pub(crate) fn castle_bk(&self) -> bool {
+        self.castle_bk
+    }
+    /// 黒のクイーンサイドキャスリング権が残っているか
+    pub(crate) fn castle_bq(&self) -> bool {
+        self.castle_bq
+    }
+
+    /// 現在の手番
+    pub(crate) fn side(&self) -> Color {
+        self.side
+    }
+
+    /// アンパッサンで実際に捕獲可能な場合のみ、そのファイルを返す
+    ///
+    /// [`Board::zobrist`]と同じ判定（[`ep_hash_file`]）を外部クレートモジュールから
+    /// 使えるようにする窓口。`ep_square`が立っているだけでは局面を区別する要素に
+    /// ならないため、Polyglotキーの計算（[`crate::opening::PolyglotBook`]）でも
+    /// 同じ判定基準を流用する
+    pub(crate) fn ep_capture_file(&self) -> Option<usize> {
+        ep_hash_file(&self.sq, self.side, self.ep_square)
+    }
+
+    /// 指定色のポーンがいるマスのビットボード
+    pub(crate) fn pawn_bitboard(&self, color: Color) -> u64 {
+        self.bb[color_index(color)][kind_index(Kind::Pawn)]
+    }
+
+    /// 現局面のポーン配置のみから求めたZobristサブキー
+    ///
+    /// 盤面全体の[`Board::zobrist`]とは独立に、ポーンの位置が一致すれば同じ値になる。
+    /// ポーン構造はポーン以外の駒の移動では変化しないため、評価側（[`crate::evaluate`]）が
+    /// このキーでポーン構造の評価結果をキャッシュし、再計算を避けられる
+    pub(crate) fn pawn_zobrist(&self) -> u64 {
+        let mut h = 0u64;
+        for &color in &[Color::White, Color::Black] {
+            let mut bb = self.pawn_bitboard(color);
+            while bb != 0 {
+                let sq = bb.trailing_zeros() as usize;
+                h ^= piece_key(color, Kind::Pawn, sq);
+                bb &= bb - 1;
+            }
+        }
+        h
+    }
     /// 指定された位置に駒を配置する
     ///
     /// # 引数
     /// * `i` - 盤面インデックス (0..63)
     /// * `p` - 配置する駒（Noneの場合は駒を取り除く）
     fn set_piece(&mut self, i: usize, p: Option<Piece>) {
+        // 既存の駒があればハッシュとビットボードから取り除く（捕獲やキャスリングの上書きも含む）
+        if let Some(old) = self.sq[i] {
+            self.zobrist ^= piece_key(old.color, old.kind, i);
+            self.bb[color_index(old.color)][kind_index(old.kind)] &= !(1u64 << i);
+            self.occ_by_color[color_index(old.color)] &= !(1u64 << i);
+        }
+        if let Some(new) = p {
+            self.zobrist ^= piece_key(new.color, new.kind, i);
+            self.bb[color_index(new.color)][kind_index(new.kind)] |= 1u64 << i;
+            self.occ_by_color[color_index(new.color)] |= 1u64 << i;
+        }
         self.sq[i] = p;
     }
 
+    /// `sq` の配置からビットボード表現（`bb`, `occ_by_color`）を再構築する
+    ///
+    /// `new`/`from_fen` など、盤面をゼロから組み立てる場所で使う
+    fn bitboards_from_sq(sq: &[Option<Piece>; 64]) -> ([[u64; 6]; 2], [u64; 2]) {
+        let mut bb = [[0u64; 6]; 2];
+        let mut occ_by_color = [0u64; 2];
+        for (i, cell) in sq.iter().enumerate() {
+            if let Some(p) = cell {
+                bb[color_index(p.color)][kind_index(p.kind)] |= 1u64 << i;
+                occ_by_color[color_index(p.color)] |= 1u64 << i;
+            }
+        }
+        (bb, occ_by_color)
+    }
+
+    /// 全駒のビットボード（両色の合併）を取得する
+    fn occupied(&self) -> u64 {
+        self.occ_by_color[0] | self.occ_by_color[1]
+    }
+
     /// 盤面をコメント形式で標準出力に表示する
     pub fn print_as_comment(&self) {
         println!(";");
@@ -215,11 +1021,30 @@ impl Board {
     /// キャスリング、アンパッサン、昇格などの特殊な手も処理し、
     /// キャスリング権やアンパッサン権、手数カウントを更新する
     ///
+    /// 変更前の状態を[`UndoInfo`]として返すので、呼び出し側は探索後に
+    /// [`Board::unmake_move`]へ渡せば盤面をクローンせずに元へ戻せる
+    ///
     /// # 引数
     /// * `m` - 適用する指し手
-    fn make_move(&mut self, m: Move) {
+    fn make_move(&mut self, m: Move) -> UndoInfo {
+        // 巻き戻し用に、変化しうる状態を事前に控えておく
+        let old_castle_wk = self.castle_wk;
+        let old_castle_wq = self.castle_wq;
+        let old_castle_bk = self.castle_bk;
+        let old_castle_bq = self.castle_bq;
+        let old_ep = self.ep_square;
+        // ハッシュに効くのは「実際に捕獲できるアンパッサン」だけなので、盤面を動かす前に
+        // （捕獲する側＝現在の手番から見て）判定しておく
+        let old_ep_hash_file = ep_hash_file(&self.sq, self.side, old_ep);
+        let old_halfmove_clock = self.halfmove_clock;
+        let old_fullmove_number = self.fullmove_number;
+        let old_zobrist = self.zobrist;
+
         // 基本適用（最低限）
-        let mut moved = self.piece_at(m.from).expect("No piece on from");
+        let moved_piece = self.piece_at(m.from).expect("No piece on from");
+        let mut moved = moved_piece;
+        let mut captured: Option<Piece> = None;
+        let mut ep_capture_square: Option<usize> = None;
         // アンパッサン
         if m.is_en_passant {
             self.set_piece(m.to, Some(moved));
@@ -228,32 +1053,36 @@ impl Board {
             let to_rank = rank_of(m.to) as isize;
             let dir = if moved.color == Color::White { -1 } else { 1 };
             let cap_sq = to_idx(file_of(m.to) as isize, to_rank + dir);
+            captured = self.piece_at(cap_sq);
+            ep_capture_square = Some(cap_sq);
             self.set_piece(cap_sq, None);
         } else if m.is_castle_kingside || m.is_castle_queenside {
-            // キャスリング
-            let (k_from, k_to, r_from, r_to) = if moved.color == Color::White {
-                if m.is_castle_kingside {
-                    (idx(4, 0), idx(6, 0), idx(7, 0), idx(5, 0))
-                } else {
-                    (idx(4, 0), idx(2, 0), idx(0, 0), idx(3, 0))
-                }
-            } else {
+            // キャスリング（Chess960対応: キング/ルークの初期ファイルは一般に
+            // e/hファイルではないので、king_start_file/rook_start_file_*から求める）
+            let rank = if moved.color == Color::White { 0 } else { 7 };
+            let k_from = idx(self.king_start_file, rank);
+            let k_to = idx(if m.is_castle_kingside { 6 } else { 2 }, rank);
+            let r_from = idx(
                 if m.is_castle_kingside {
-                    (idx(4, 7), idx(6, 7), idx(7, 7), idx(5, 7))
+                    self.rook_start_file_k
                 } else {
-                    (idx(4, 7), idx(2, 7), idx(0, 7), idx(3, 7))
-                }
-            };
-            // king
+                    self.rook_start_file_q
+                },
+                rank,
+            );
+            let r_to = idx(if m.is_castle_kingside { 5 } else { 3 }, rank);
+
+            // king/rookの移動先がもう一方の移動元と重なりうる（Chess960）ので、
+            // 先に両方の移動元を空にしてから移動先に置く
             let king = self.piece_at(k_from).unwrap();
-            self.set_piece(k_from, None);
-            self.set_piece(k_to, Some(king));
-            // rook
             let rook = self.piece_at(r_from).unwrap();
+            self.set_piece(k_from, None);
             self.set_piece(r_from, None);
+            self.set_piece(k_to, Some(king));
             self.set_piece(r_to, Some(rook));
         } else {
             // 通常
+            captured = self.piece_at(m.to);
             self.set_piece(m.to, Some(moved));
             self.set_piece(m.from, None);
         }
@@ -264,43 +1093,48 @@ impl Board {
             self.set_piece(m.to, Some(moved));
         }
 
-        // キャスリング権の更新（キング/ルークが動いたら消す）
+        // キャスリング権の更新（キング/ルークが動いたら消す。ファイルは
+        // king_start_file/rook_start_file_*で一般化されているのでChess960でも正しい）
+        let white_rook_q = idx(self.rook_start_file_q, 0);
+        let white_rook_k = idx(self.rook_start_file_k, 0);
+        let black_rook_q = idx(self.rook_start_file_q, 7);
+        let black_rook_k = idx(self.rook_start_file_k, 7);
         match moved.color {
             Color::White => {
                 // 白キング・白ルークの移動/捕獲で権利を消す
-                if m.from == idx(4, 0) {
+                if m.from == idx(self.king_start_file, 0) {
                     self.castle_wk = false;
                     self.castle_wq = false;
                 }
-                if m.from == idx(0, 0) || m.to == idx(0, 0) {
+                if m.from == white_rook_q || m.to == white_rook_q {
                     self.castle_wq = false;
                 }
-                if m.from == idx(7, 0) || m.to == idx(7, 0) {
+                if m.from == white_rook_k || m.to == white_rook_k {
                     self.castle_wk = false;
                 }
                 // 黒ルークが取られたら黒権利調整
-                if m.to == idx(0, 7) {
+                if m.to == black_rook_q {
                     self.castle_bq = false;
                 }
-                if m.to == idx(7, 7) {
+                if m.to == black_rook_k {
                     self.castle_bk = false;
                 }
             }
             Color::Black => {
-                if m.from == idx(4, 7) {
+                if m.from == idx(self.king_start_file, 7) {
                     self.castle_bk = false;
                     self.castle_bq = false;
                 }
-                if m.from == idx(0, 7) || m.to == idx(0, 7) {
+                if m.from == black_rook_q || m.to == black_rook_q {
                     self.castle_bq = false;
                 }
-                if m.from == idx(7, 7) || m.to == idx(7, 7) {
+                if m.from == black_rook_k || m.to == black_rook_k {
                     self.castle_bk = false;
                 }
-                if m.to == idx(0, 0) {
+                if m.to == white_rook_q {
                     self.castle_wq = false;
                 }
-                if m.to == idx(7, 0) {
+                if m.to == white_rook_k {
                     self.castle_wk = false;
                 }
             }
@@ -318,8 +1152,9 @@ impl Board {
             }
         }
 
-        // 手数カウント（50手ルール用の半手）: ここでは参考値として動かすだけ
-        if moved.kind == Kind::Pawn || m.is_capture {
+        // 手数カウント（50手ルール用の半手）。昇格手も含めてポーンの移動は不可逆なので、
+        // 昇格で上書きされる前の`moved_piece.kind`で判定する
+        if moved_piece.kind == Kind::Pawn || m.is_capture {
             self.halfmove_clock = 0;
         } else {
             self.halfmove_clock += 1;
@@ -329,6 +1164,137 @@ impl Board {
             self.fullmove_number += 1;
         }
         self.side = Board::other(self.side);
+
+        // Zobrist差分更新: キャスリング権・アンパッサン・手番
+        if old_castle_wk != self.castle_wk {
+            self.zobrist ^= castle_key(0);
+        }
+        if old_castle_wq != self.castle_wq {
+            self.zobrist ^= castle_key(1);
+        }
+        if old_castle_bk != self.castle_bk {
+            self.zobrist ^= castle_key(2);
+        }
+        if old_castle_bq != self.castle_bq {
+            self.zobrist ^= castle_key(3);
+        }
+        if let Some(file) = old_ep_hash_file {
+            self.zobrist ^= ep_key(file);
+        }
+        // 新しいep_squareの捕獲可能性は、手を適用し手番を切り替えた後の盤面
+        // （捕獲しうる側＝新しい手番）で判定する
+        if let Some(file) = ep_hash_file(&self.sq, self.side, self.ep_square) {
+            self.zobrist ^= ep_key(file);
+        }
+        self.zobrist ^= side_key();
+
+        // 不可逆手（ポーンの移動・捕獲）より前の局面は二度と現れ得ないので履歴を捨てる
+        let history_was_reset = self.halfmove_clock == 0;
+        let old_history = if history_was_reset {
+            std::mem::take(&mut self.history)
+        } else {
+            Vec::new()
+        };
+        self.history.push(self.zobrist);
+
+        UndoInfo {
+            moved_piece,
+            captured,
+            ep_capture_square,
+            old_castle_wk,
+            old_castle_wq,
+            old_castle_bk,
+            old_castle_bq,
+            old_ep_square: old_ep,
+            old_halfmove_clock,
+            old_fullmove_number,
+            old_zobrist,
+            history_was_reset,
+            old_history,
+        }
+    }
+
+    /// `make_move`で適用した指し手を取り消し、盤面を元の状態に戻す
+    ///
+    /// 駒の配置・キャスリング権・アンパッサン権・手数カウント・Zobristハッシュ・
+    /// 千日手履歴のすべてを`undo`に記録された値から復元する。昇格手は昇格前の
+    /// ポーンに、アンパッサンは捕獲されたポーンを実際にいたマスに戻す
+    ///
+    /// # 引数
+    /// * `m` - 取り消す指し手（`make_move`に渡したものと同一である必要がある）
+    /// * `undo` - 対応する`make_move`呼び出しが返した巻き戻し情報
+    fn unmake_move(&mut self, m: Move, undo: UndoInfo) {
+        self.side = Board::other(self.side);
+
+        // 駒の配置を戻す。set_pieceは呼ぶたびにzobrist/ビットボードを差分更新するが、
+        // 最後にzobristをundo.old_zobristでまとめて上書きするので中間状態は気にしなくてよい
+        if m.is_en_passant {
+            self.set_piece(m.from, Some(undo.moved_piece));
+            self.set_piece(m.to, None);
+            if let Some(cap_sq) = undo.ep_capture_square {
+                self.set_piece(cap_sq, undo.captured);
+            }
+        } else if m.is_castle_kingside || m.is_castle_queenside {
+            let rank = if undo.moved_piece.color == Color::White { 0 } else { 7 };
+            let k_from = idx(self.king_start_file, rank);
+            let k_to = idx(if m.is_castle_kingside { 6 } else { 2 }, rank);
+            let r_from = idx(
+                if m.is_castle_kingside {
+                    self.rook_start_file_k
+                } else {
+                    self.rook_start_file_q
+                },
+                rank,
+            );
+            let r_to = idx(if m.is_castle_kingside { 5 } else { 3 }, rank);
+
+            let king = self.piece_at(k_to).expect("king missing at castle destination");
+            let rook = self.piece_at(r_to).expect("rook missing at castle destination");
+            self.set_piece(k_to, None);
+            self.set_piece(r_to, None);
+            self.set_piece(k_from, Some(king));
+            self.set_piece(r_from, Some(rook));
+        } else {
+            // 昇格していてもundo.moved_pieceは昇格前のポーンなので、これを戻すだけでよい
+            self.set_piece(m.from, Some(undo.moved_piece));
+            self.set_piece(m.to, undo.captured);
+        }
+
+        self.castle_wk = undo.old_castle_wk;
+        self.castle_wq = undo.old_castle_wq;
+        self.castle_bk = undo.old_castle_bk;
+        self.castle_bq = undo.old_castle_bq;
+        self.ep_square = undo.old_ep_square;
+        self.halfmove_clock = undo.old_halfmove_clock;
+        self.fullmove_number = undo.old_fullmove_number;
+
+        if undo.history_was_reset {
+            self.history = undo.old_history;
+        } else {
+            self.history.pop();
+        }
+
+        // 差分更新の誤差を避けるため、zobristは最後にまとめて元の値へ戻す
+        self.zobrist = undo.old_zobrist;
+    }
+
+    /// 現局面が千日手（同一局面が3回出現）かどうかを判定する
+    ///
+    /// 直前の不可逆手（ポーンの移動・駒の捕獲）以降の履歴のみを数えるため、
+    /// `history` はそうした手が起きるたびにクリアされている
+    pub fn is_threefold_repetition(&self) -> bool {
+        self.history.iter().filter(|&&h| h == self.zobrist).count() >= 3
+    }
+
+    /// 現局面が50手ルールによる引き分けかどうかを判定する（半手数が100以上、すなわち
+    /// 不可逆手なしで双方50手ずつ指した）
+    pub fn is_fifty_move_draw(&self) -> bool {
+        self.halfmove_clock >= 100
+    }
+
+    /// 現局面が引き分け（千日手または50手ルール）かどうかを判定する
+    pub fn is_draw(&self) -> bool {
+        self.is_fifty_move_draw() || self.is_threefold_repetition()
     }
 
     // ============ ここから指し手解釈（UCI/LAN 先、SAN 簡易後） ============
@@ -382,22 +1348,9 @@ impl Board {
     /// # 引数
     /// * `kingside` - true ならキングサイド、false ならクイーンサイド
     fn build_castle(&self, kingside: bool) -> Result<Move, String> {
-        let (from, to) = match self.side {
-            Color::White => {
-                if kingside {
-                    (idx(4, 0), idx(6, 0))
-                } else {
-                    (idx(4, 0), idx(2, 0))
-                }
-            }
-            Color::Black => {
-                if kingside {
-                    (idx(4, 7), idx(6, 7))
-                } else {
-                    (idx(4, 7), idx(2, 7))
-                }
-            }
-        };
+        let rank = if self.side == Color::White { 0 } else { 7 };
+        let from = idx(self.king_start_file, rank);
+        let to = idx(if kingside { 6 } else { 2 }, rank);
         Ok(Move {
             from,
             to,
@@ -409,6 +1362,34 @@ impl Board {
         })
     }
 
+    /// 昇格指定の整合性を確認する
+    ///
+    /// `from`の駒がポーンで`to`が昇格段（白なら8段目、黒なら1段目）に到達する手は
+    /// 必ず昇格先を指定しなければならず、それ以外の手では昇格先を指定できない
+    /// （`Kind::Queen/Rook/Bishop/Knight`以外は呼び出し元のパーサーがそもそも
+    /// 解析できないので、キング・ポーンへの昇格はここまで来ない）
+    fn validate_promotion(&self, from: usize, to: usize, promo: Option<Kind>) -> Result<(), String> {
+        let is_promotion_move = matches!(self.piece_at(from), Some(p) if p.kind == Kind::Pawn)
+            && rank_of(to) == if self.side == Color::White { 7 } else { 0 };
+
+        match (is_promotion_move, promo) {
+            (true, None) => {
+                let square_to_str = |i: usize| {
+                    format!("{}{}", (b'a' + file_of(i) as u8) as char, (b'1' + rank_of(i) as u8) as char)
+                };
+                Err(format!(
+                    "Pawn move {}{} reaches the last rank and requires a promotion piece",
+                    square_to_str(from),
+                    square_to_str(to)
+                ))
+            }
+            (false, Some(_)) => {
+                Err("Promotion specifier is only valid on a pawn move to the last rank".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+
     /// UCI/LAN形式の指し手の解析を試みる
     ///
     /// "e2e4", "e7e8Q" などの形式を解析する
@@ -432,6 +1413,33 @@ impl Board {
         let from = idx((b[0] - b'a') as usize, (b[1] - b'1') as usize);
         let to = idx((b[2] - b'a') as usize, (b[3] - b'1') as usize);
 
+        // UCIプロトコルではキャスリングもキングの2マス移動として送られてくるので、
+        // ここで検出して専用フラグを立てる（そうしないとルークが動かない）。
+        // Chess960では「キングが自分のルークを取る」形式のエンコーディングも使われるため、
+        // それも検出する
+        if let Some(p) = self.piece_at(from) {
+            if p.kind == Kind::King {
+                let home_rank = if p.color == Color::White { 0 } else { 7 };
+                if rank_of(from) == home_rank
+                    && from == idx(self.king_start_file, home_rank)
+                    && rank_of(to) == home_rank
+                {
+                    if to == idx(6, home_rank) {
+                        return Ok(Some(self.build_castle(true)?));
+                    }
+                    if to == idx(2, home_rank) {
+                        return Ok(Some(self.build_castle(false)?));
+                    }
+                    if let Some(target) = self.piece_at(to) {
+                        if target.kind == Kind::Rook && target.color == p.color {
+                            let kingside = file_of(to) == self.rook_start_file_k;
+                            return Ok(Some(self.build_castle(kingside)?));
+                        }
+                    }
+                }
+            }
+        }
+
         let promo = if t.len() >= 5 {
             match t.as_bytes()[4] as char {
                 'q' | 'Q' => Some(Kind::Queen),
@@ -454,6 +1462,8 @@ impl Board {
             }
         }
 
+        self.validate_promotion(from, to, promo)?;
+
         Ok(Some(Move {
             from,
             to,
@@ -465,6 +1475,24 @@ impl Board {
         }))
     }
 
+    /// SAN（標準代数記法）形式の指し手を解析し、対応する手を返す
+    ///
+    /// "Nf3", "exd5", "e8=Q", "O-O"/"O-O-O" などの表記に対応する。
+    /// キャスリング以外は[`Board::parse_san_and_find_move`]に委譲する
+    ///
+    /// # 引数
+    /// * `t` - SAN形式の指し手文字列
+    pub fn parse_san(&self, t: &str) -> Result<Move, String> {
+        let s = t.trim();
+        if s == "O-O" || s == "0-0" {
+            return self.build_castle(true);
+        }
+        if s == "O-O-O" || s == "0-0-0" {
+            return self.build_castle(false);
+        }
+        self.parse_san_and_find_move(s)
+    }
+
     /// SAN形式の指し手を解析して対応する手を見つける
     ///
     /// "Nf3", "exd5", "O-O" などの標準代数記法を解析し、
@@ -563,6 +1591,8 @@ impl Board {
             is_ep = true;
         }
 
+        self.validate_promotion(from, to, promo)?;
+
         Ok(Move {
             from,
             to,
@@ -598,8 +1628,10 @@ impl Board {
 
     /// 現在の局面における全ての合法手を生成する
     ///
-    /// 自玉がチェックに晒される手は除外される
-    fn generate_legal_moves(&self) -> Vec<Move> {
+    /// 自玉がチェックに晒される手は除外される。`is_legal_move`がmake→unmakeで
+    /// 判定するため、このメソッド自体も`&mut self`を取る（盤面はクローンせず、
+    /// 呼び出し終了時には元の状態に戻っている）
+    fn generate_legal_moves(&mut self) -> Vec<Move> {
         let mut moves = Vec::new();
 
         for from in 0..64 {
@@ -620,10 +1652,13 @@ impl Board {
         }
 
         // チェックに晒す手を除外
-        moves
-            .into_iter()
-            .filter(|&m| self.is_legal_move(m))
-            .collect()
+        let mut legal = Vec::with_capacity(moves.len());
+        for m in moves {
+            if self.is_legal_move(m) {
+                legal.push(m);
+            }
+        }
+        legal
     }
 
     /// ポーンの合法手候補を生成する
@@ -779,54 +1814,30 @@ impl Board {
 
     /// 長距離駒（ビショップ、ルーク、クイーン）の合法手候補を生成する
     ///
+    /// レイを1マスずつ辿る代わりに、呼び出し側がマジックビットボード（[`magic`]モジュール）で
+    /// 求めた利き（`attacks`）を受け取り、自駒のいるマスを除いて手を生成する
+    ///
     /// # 引数
     /// * `from` - 駒の位置
-    /// * `directions` - 移動方向のリスト（ファイル差、ランク差）
+    /// * `attacks` - `from`からの利きビットボード（自駒・敵駒を問わず遮られるまでの全マス）
     /// * `moves` - 生成した手を追加するベクタ
-    fn generate_sliding_moves(
-        &self,
-        from: usize,
-        directions: &[(isize, isize)],
-        moves: &mut Vec<Move>,
-    ) {
-        let f = file_of(from) as isize;
-        let r = rank_of(from) as isize;
-
-        for &(df, dr) in directions {
-            let mut cur_f = f + df;
-            let mut cur_r = r + dr;
-
-            while in_bounds(cur_f, cur_r) {
-                let to = to_idx(cur_f, cur_r);
-
-                if let Some(target) = self.piece_at(to) {
-                    if target.color != self.side {
-                        moves.push(Move {
-                            from,
-                            to,
-                            promo: None,
-                            is_capture: true,
-                            is_en_passant: false,
-                            is_castle_kingside: false,
-                            is_castle_queenside: false,
-                        });
-                    }
-                    break; // 駒があったら止まる
-                } else {
-                    moves.push(Move {
-                        from,
-                        to,
-                        promo: None,
-                        is_capture: false,
-                        is_en_passant: false,
-                        is_castle_kingside: false,
-                        is_castle_queenside: false,
-                    });
-                }
-
-                cur_f += df;
-                cur_r += dr;
-            }
+    fn generate_sliding_moves(&self, from: usize, attacks: u64, moves: &mut Vec<Move>) {
+        let own = self.occ_by_color[color_index(self.side)];
+        let mut targets = attacks & !own;
+
+        while targets != 0 {
+            let to = targets.trailing_zeros() as usize;
+            let is_capture = self.piece_at(to).is_some();
+            moves.push(Move {
+                from,
+                to,
+                promo: None,
+                is_capture,
+                is_en_passant: false,
+                is_castle_kingside: false,
+                is_castle_queenside: false,
+            });
+            targets &= targets - 1;
         }
     }
 
@@ -836,8 +1847,8 @@ impl Board {
     /// * `from` - ビショップの位置
     /// * `moves` - 生成した手を追加するベクタ
     fn generate_bishop_moves(&self, from: usize, moves: &mut Vec<Move>) {
-        let diagonals = [(-1, -1), (-1, 1), (1, -1), (1, 1)];
-        self.generate_sliding_moves(from, &diagonals, moves);
+        let attacks = magic::bishop_attacks(from, self.occupied());
+        self.generate_sliding_moves(from, attacks, moves);
     }
 
     /// ルークの合法手候補を生成する
@@ -846,8 +1857,8 @@ impl Board {
     /// * `from` - ルークの位置
     /// * `moves` - 生成した手を追加するベクタ
     fn generate_rook_moves(&self, from: usize, moves: &mut Vec<Move>) {
-        let orthogonals = [(-1, 0), (1, 0), (0, -1), (0, 1)];
-        self.generate_sliding_moves(from, &orthogonals, moves);
+        let attacks = magic::rook_attacks(from, self.occupied());
+        self.generate_sliding_moves(from, attacks, moves);
     }
 
     /// クイーンの合法手候補を生成する
@@ -856,17 +1867,8 @@ impl Board {
     /// * `from` - クイーンの位置
     /// * `moves` - 生成した手を追加するベクタ
     fn generate_queen_moves(&self, from: usize, moves: &mut Vec<Move>) {
-        let all_directions = [
-            (-1, -1),
-            (-1, 0),
-            (-1, 1),
-            (0, -1),
-            (0, 1),
-            (1, -1),
-            (1, 0),
-            (1, 1),
-        ];
-        self.generate_sliding_moves(from, &all_directions, moves);
+        let attacks = magic::queen_attacks(from, self.occupied());
+        self.generate_sliding_moves(from, attacks, moves);
     }
 
     /// キングの合法手候補を生成する
@@ -972,13 +1974,7 @@ impl Board {
     /// # 引数
     /// * `color` - キャスリングする側の色
     fn can_castle_kingside(&self, color: Color) -> bool {
-        let rank = if color == Color::White { 0 } else { 7 };
-        // f, g マスが空で、e, f, g が攻撃されていない
-        self.piece_at(idx(5, rank)).is_none()
-            && self.piece_at(idx(6, rank)).is_none()
-            && !self.is_square_attacked(idx(4, rank), Board::other(color))
-            && !self.is_square_attacked(idx(5, rank), Board::other(color))
-            && !self.is_square_attacked(idx(6, rank), Board::other(color))
+        self.can_castle(color, true)
     }
 
     /// クイーンサイドキャスリングが可能かチェックする
@@ -988,14 +1984,44 @@ impl Board {
     /// # 引数
     /// * `color` - キャスリングする側の色
     fn can_castle_queenside(&self, color: Color) -> bool {
+        self.can_castle(color, false)
+    }
+
+    /// キャスリングの可否を判定する（Chess960対応）
+    ///
+    /// キング・ルークの初期ファイルが標準（e/h・a）と異なっていてもよいように、
+    /// `king_start_file`/`rook_start_file_*` から経路と最終位置を求めて判定する。
+    /// キングとルークそれぞれの移動経路上のマスは、互いの出発マスを除いてすべて
+    /// 空でなければならず、さらにキングの通過マス（出発地点含む）は相手に
+    /// 攻撃されていてはならない
+    ///
+    /// # 引数
+    /// * `color` - キャスリングする側の色
+    /// * `kingside` - キングサイドなら`true`、クイーンサイドなら`false`
+    fn can_castle(&self, color: Color, kingside: bool) -> bool {
         let rank = if color == Color::White { 0 } else { 7 };
-        // b, c, d マスが空で、c, d, e が攻撃されていない
-        self.piece_at(idx(1, rank)).is_none()
-            && self.piece_at(idx(2, rank)).is_none()
-            && self.piece_at(idx(3, rank)).is_none()
-            && !self.is_square_attacked(idx(2, rank), Board::other(color))
-            && !self.is_square_attacked(idx(3, rank), Board::other(color))
-            && !self.is_square_attacked(idx(4, rank), Board::other(color))
+        let king_from = idx(self.king_start_file, rank);
+        let rook_from = idx(
+            if kingside {
+                self.rook_start_file_k
+            } else {
+                self.rook_start_file_q
+            },
+            rank,
+        );
+        let king_to = idx(if kingside { 6 } else { 2 }, rank);
+        let rook_to = idx(if kingside { 5 } else { 3 }, rank);
+
+        let path_clear = |from: usize, to: usize| {
+            let (lo, hi) = (from.min(to), from.max(to));
+            (lo..=hi).all(|sq| sq == king_from || sq == rook_from || self.piece_at(sq).is_none())
+        };
+        if !path_clear(king_from, king_to) || !path_clear(rook_from, rook_to) {
+            return false;
+        }
+
+        let (lo, hi) = (king_from.min(king_to), king_from.max(king_to));
+        !(lo..=hi).any(|sq| self.is_square_attacked(sq, Board::other(color)))
     }
 
     /// 指定されたマスが指定された色の駒に攻撃されているかチェックする
@@ -1004,106 +2030,81 @@ impl Board {
     /// * `square` - チェックするマス
     /// * `by_color` - 攻撃側の色
     fn is_square_attacked(&self, square: usize, by_color: Color) -> bool {
-        // 指定されたマスが指定された色の駒に攻撃されているかチェック
-        for from in 0..64 {
-            if let Some(piece) = self.piece_at(from) {
-                if piece.color == by_color && self.can_attack(from, square) {
-                    return true;
-                }
-            }
-        }
-        false
+        self.attacked_by(by_color) & (1u64 << square) != 0
     }
 
-    /// 指定された位置の駒が別の位置を攻撃できるかチェックする
+    /// 指定された色が利かせているマス全体をビットボードで返す
     ///
-    /// # 引数
-    /// * `from` - 攻撃元の位置
-    /// * `to` - 攻撃先の位置
-    fn can_attack(&self, from: usize, to: usize) -> bool {
-        if from == to {
-            return false;
-        }
-        let piece = match self.piece_at(from) {
-            Some(p) => p,
-            None => return false,
-        };
-
-        match piece.kind {
-            Kind::Pawn => {
-                let forward = if piece.color == Color::White { 1 } else { -1 };
-                let f_from = file_of(from) as isize;
-                let r_from = rank_of(from) as isize;
-                let f_to = file_of(to) as isize;
-                let r_to = rank_of(to) as isize;
+    /// ポーン・ナイト・キングはビット演算と事前計算済みテーブルで、スライディング駒
+    /// （ビショップ/ルーク/クイーン）はマジックビットボード（[`magic`]モジュール）で求め、
+    /// すべてOR結合する。
+    /// `king_bb & attacked_by(相手の色) == 0` でキングの安全確認ができるようにするための利きマスク
+    pub(crate) fn attacked_by(&self, by_color: Color) -> u64 {
+        let tables = leaper_attack_tables();
+        let occupied = self.occupied();
+        let c = color_index(by_color);
+        let mut attacks = 0u64;
+
+        // ポーン: 白は1つ上のランク斜め、黒は1つ下のランク斜めを攻撃する
+        let pawns = self.bb[c][kind_index(Kind::Pawn)];
+        const NOT_FILE_A: u64 = 0xfefefefefefefefe;
+        const NOT_FILE_H: u64 = 0x7f7f7f7f7f7f7f7f;
+        attacks |= if by_color == Color::White {
+            ((pawns & NOT_FILE_A) << 7) | ((pawns & NOT_FILE_H) << 9)
+        } else {
+            ((pawns & NOT_FILE_H) >> 7) | ((pawns & NOT_FILE_A) >> 9)
+        };
 
-                // ポーンは斜め前方のマスを攻撃
-                r_to == r_from + forward && (f_to - f_from).abs() == 1
-            }
-            Kind::Knight => {
-                let df = (file_of(from) as isize - file_of(to) as isize).abs();
-                let dr = (rank_of(from) as isize - rank_of(to) as isize).abs();
-                (df == 1 && dr == 2) || (df == 2 && dr == 1)
-            }
-            Kind::King => {
-                let df = (file_of(from) as isize - file_of(to) as isize).abs();
-                let dr = (rank_of(from) as isize - rank_of(to) as isize).abs();
-                df <= 1 && dr <= 1
-            }
-            Kind::Bishop => {
-                let df = file_of(to) as isize - file_of(from) as isize;
-                let dr = rank_of(to) as isize - rank_of(from) as isize;
-                if df.abs() != dr.abs() || df == 0 {
-                    return false;
-                }
-                let stepf = df.signum();
-                let stepr = dr.signum();
-                self.line_clear(from, to, stepf, stepr)
-            }
-            Kind::Rook => {
-                let df = file_of(to) as isize - file_of(from) as isize;
-                let dr = rank_of(to) as isize - rank_of(from) as isize;
-                if !(df == 0 || dr == 0) {
-                    return false;
-                }
-                let stepf = df.signum();
-                let stepr = dr.signum();
-                if df == 0 && dr == 0 {
-                    return false;
-                }
-                self.line_clear(from, to, stepf, stepr)
-            }
-            Kind::Queen => {
-                let df = file_of(to) as isize - file_of(from) as isize;
-                let dr = rank_of(to) as isize - rank_of(from) as isize;
-                if !(df == 0 || dr == 0 || df.abs() == dr.abs()) {
-                    return false;
-                }
-                let stepf = df.signum();
-                let stepr = dr.signum();
-                self.line_clear(from, to, stepf, stepr)
-            }
+        // ナイト・キング: 事前計算テーブルを参照
+        let mut knights = self.bb[c][kind_index(Kind::Knight)];
+        while knights != 0 {
+            let sq = knights.trailing_zeros() as usize;
+            attacks |= tables.knight[sq];
+            knights &= knights - 1;
+        }
+        let mut kings = self.bb[c][kind_index(Kind::King)];
+        while kings != 0 {
+            let sq = kings.trailing_zeros() as usize;
+            attacks |= tables.king[sq];
+            kings &= kings - 1;
+        }
+
+        // スライディング駒: マジックビットボードでレイ走査を代替
+        let mut bishops = self.bb[c][kind_index(Kind::Bishop)] | self.bb[c][kind_index(Kind::Queen)];
+        while bishops != 0 {
+            let sq = bishops.trailing_zeros() as usize;
+            attacks |= magic::bishop_attacks(sq, occupied);
+            bishops &= bishops - 1;
+        }
+        let mut rooks = self.bb[c][kind_index(Kind::Rook)] | self.bb[c][kind_index(Kind::Queen)];
+        while rooks != 0 {
+            let sq = rooks.trailing_zeros() as usize;
+            attacks |= magic::rook_attacks(sq, occupied);
+            rooks &= rooks - 1;
         }
+
+        attacks
     }
 
     /// 指し手が合法かどうかをチェックする
     ///
-    /// 実際に手を指してみて、自玉がチェックに晒されないことを確認
+    /// 実際に手を指してみて、自玉がチェックに晒されないことを確認する。
+    /// 盤面をクローンする代わりに`make_move`→判定→`unmake_move`で同じ盤面に
+    /// 適用・復元するので、呼び出し後の`self`は呼び出し前と同じ状態に戻る
     ///
     /// # 引数
     /// * `m` - チェックする手
-    fn is_legal_move(&self, m: Move) -> bool {
-        // 手を実際に指してみてキングがチェックに晒されないかチェック
+    fn is_legal_move(&mut self, m: Move) -> bool {
         let current_side = self.side;
-        let mut temp_board = self.clone();
-        temp_board.make_move(m);
+        let undo = self.make_move(m);
         // make_moveが手番を切り替えるので、元の手番のキングを探す
-        if let Some(king_square) = temp_board.find_king(current_side) {
-            !temp_board.is_square_attacked(king_square, Board::other(current_side))
-        } else {
+        let legal = match self.find_king(current_side) {
+            Some(king_square) => !self.is_square_attacked(king_square, Board::other(current_side)),
             // キングが見つからない場合は不正な手
-            false
-        }
+            None => false,
+        };
+        self.unmake_move(m, undo);
+        legal
     }
 
     /// 指定された色のキングの位置を探す
@@ -1123,36 +2124,23 @@ impl Board {
 
     /// 局面を評価する
     ///
-    /// 駒の価値の合計に基づいて評価値を計算する
-    /// 白側から見て正の値が有利、負の値が不利
+    /// `crate::evaluate` のプラガブルな評価関数（デフォルトはPiece-Square Tablesを
+    /// 使うテーパード評価）に委譲する。`crate::evaluate::evaluate`は白側から見て
+    /// 正の値が有利だが、negamax探索は手番側から見た評価値を前提にするため、
+    /// 黒の手番では符号を反転して返す
     fn evaluate(&self) -> i32 {
-        let mut score = 0;
-
-        for i in 0..64 {
-            if let Some(piece) = self.piece_at(i) {
-                let value = match piece.kind {
-                    Kind::Pawn => 1,
-                    Kind::Knight => 3,
-                    Kind::Bishop => 3,
-                    Kind::Rook => 5,
-                    Kind::Queen => 9,
-                    Kind::King => 999,
-                };
-
-                match piece.color {
-                    Color::White => score += value,
-                    Color::Black => score -= value,
-                }
-            }
+        let score = evaluate::evaluate(self);
+        if self.side == Color::White {
+            score
+        } else {
+            -score
         }
-
-        score
     }
 
     /// チェックメイトかどうかを判定する
     ///
     /// 手番側のキングがチェックされており、合法手がない状態
-    fn is_checkmate(&self) -> bool {
+    fn is_checkmate(&mut self) -> bool {
         if let Some(king_square) = self.find_king(self.side) {
             self.is_square_attacked(king_square, Board::other(self.side))
                 && self.generate_legal_moves().is_empty()
@@ -1164,7 +2152,7 @@ impl Board {
     /// ステイルメイトかどうかを判定する
     ///
     /// 手番側のキングがチェックされておらず、合法手がない状態
-    fn is_stalemate(&self) -> bool {
+    fn is_stalemate(&mut self) -> bool {
         if let Some(king_square) = self.find_king(self.side) {
             !self.is_square_attacked(king_square, Board::other(self.side))
                 && self.generate_legal_moves().is_empty()
@@ -1175,9 +2163,9 @@ impl Board {
 
     /// ゲームが終了しているかを判定する
     ///
-    /// チェックメイトまたはステイルメイトの場合に true
-    fn is_game_over(&self) -> bool {
-        self.is_checkmate() || self.is_stalemate()
+    /// チェックメイト、ステイルメイト、または（50手ルール・千日手による）引き分けの場合に true
+    fn is_game_over(&mut self) -> bool {
+        self.is_checkmate() || self.is_stalemate() || self.is_draw()
     }
 
     /// 反復深化探索で最適な手を見つける
@@ -1190,7 +2178,7 @@ impl Board {
     ///
     /// # 戻り値
     /// 最適手（合法手がない場合はNone）
-    pub fn find_best_move(&self, timeout: Duration) -> Option<Move> {
+    pub fn find_best_move(&mut self, timeout: Duration, threads: Option<usize>) -> Option<Move> {
         let moves = self.generate_legal_moves();
         if moves.is_empty() {
             return None;
@@ -1199,10 +2187,19 @@ impl Board {
         let start_time = Instant::now();
         let mut best_move = moves[0];
         let mut current_depth = 1;
+        // 反復深化の全イテレーションで使い回す置換表。複数ワーカーから共有するためMutexで保護する
+        let tt = Mutex::new(TranspositionTable::new());
+        let num_threads = threads.unwrap_or(1).max(1);
 
         loop {
-            // 各深度での探索
-            if let Some(result) = self.search_at_depth(current_depth, start_time, timeout) {
+            // 各深度での探索（スレッド数が1なら直列、2以上ならルートを分担する並列探索）
+            let result = if num_threads <= 1 {
+                self.search_at_depth(current_depth, &tt, start_time, timeout)
+            } else {
+                self.search_at_depth_parallel(current_depth, &tt, start_time, timeout, num_threads)
+            };
+
+            if let Some(result) = result {
                 best_move = result;
                 eprintln!("; Completed depth {} (elapsed: {:.2}s)",
                          current_depth,
@@ -1227,24 +2224,58 @@ impl Board {
         Some(best_move)
     }
 
-    /// 指定深度で最適な手を探索する
+    /// 指定された固定深度まで（反復深化せずに）探索して最善手を返す
+    ///
+    /// UCIの `go depth N` に応えるための薄いラッパーで、置換表は今回の探索専用に新規作成する
+    ///
+    /// # 引数
+    /// * `depth` - 探索深度
+    /// * `timeout` - 探索の制限時間（これを超えたら打ち切ってNoneを返す）
+    pub fn search_fixed_depth(&mut self, depth: u32, timeout: Duration) -> Option<Move> {
+        let tt = Mutex::new(TranspositionTable::new());
+        self.search_at_depth(depth, &tt, Instant::now(), timeout)
+    }
+
+    /// 指定深度で最適な手を探索する（ルートノード）
+    ///
+    /// 置換表にこの局面の最善手があれば先頭に持ってきて探索し、
+    /// 結果を置換表へ格納して以降の探索やイテレーションから再利用できるようにする
     ///
     /// # 引数
     /// * `depth` - 探索深度
+    /// * `tt` - 置換表
     /// * `start_time` - 探索開始時刻
     /// * `timeout` - 探索の制限時間
     ///
     /// # 戻り値
     /// タイムアウト前に完了した場合は最適手、タイムアウトした場合はNone
-    fn search_at_depth(&self, depth: u32, start_time: Instant, timeout: Duration) -> Option<Move> {
-        let moves = self.generate_legal_moves();
+    fn search_at_depth(
+        &mut self,
+        depth: u32,
+        tt: &Mutex<TranspositionTable>,
+        start_time: Instant,
+        timeout: Duration,
+    ) -> Option<Move> {
+        let mut moves = self.generate_legal_moves();
         if moves.is_empty() {
             return None;
         }
 
-        let maximizing = self.side == Color::White;
+        // 捕獲する手を先に試すと枝刈りが効きやすいので並べ替える
+        moves.sort_by_key(|m| !m.is_capture);
+
+        // 置換表の最善手を先頭に並べ替え、枝刈り効率を上げる
+        if let Some(entry) = tt.lock().unwrap().probe(self.zobrist()) {
+            if let Some(best) = entry.best_move {
+                if let Some(pos) = moves.iter().position(|&mv| mv == best) {
+                    moves.swap(0, pos);
+                }
+            }
+        }
+
         let mut best_move = moves[0];
-        let mut best_eval = if maximizing { -100001 } else { 100001 };
+        let mut alpha = -100001;
+        let beta = 100001;
 
         for m in moves {
             // タイムアウトチェック
@@ -1252,72 +2283,375 @@ impl Board {
                 return None;
             }
 
-            let mut board_copy = self.clone();
-            board_copy.make_move(m);
-            let eval = board_copy.minimax(depth - 1, !maximizing, start_time, timeout)?;
+            // 盤面をクローンせず、make→再帰→unmakeで同じ盤面を使い回す
+            let undo = self.make_move(m);
+            let result = self.minimax(depth - 1, -beta, -alpha, tt, start_time, timeout);
+            self.unmake_move(m, undo);
+            // 子局面は相手視点の評価値を返すので、符号を反転して自分視点に戻す
+            let eval = match result {
+                Some(score) => -score,
+                None => return None,
+            };
 
-            if maximizing && eval > best_eval {
-                best_eval = eval;
-                best_move = m;
-            } else if !maximizing && eval < best_eval {
-                best_eval = eval;
+            if eval > alpha {
+                alpha = eval;
                 best_move = m;
             }
         }
 
+        tt.lock()
+            .unwrap()
+            .store(self.zobrist(), depth, alpha, TTFlag::Exact, Some(best_move));
+
+        Some(best_move)
+    }
+
+    /// 複数ワーカースレッドでルートの指し手を分担して探索する（ルート分割による並列化）
+    ///
+    /// crossbeamのスコープ付きスレッドでルートの合法手を等分し、各ワーカーが
+    /// それぞれの持ち分をアルファベータ探索する。置換表はMutexで保護して全ワーカーに
+    /// 共有し、互いの探索結果（深さ・最善手）を再利用できるようにする
+    ///
+    /// # 引数
+    /// * `depth` - 探索深度
+    /// * `tt` - 共有置換表
+    /// * `start_time` - 探索開始時刻
+    /// * `timeout` - 探索の制限時間
+    /// * `threads` - ワーカースレッド数（2以上を想定）
+    fn search_at_depth_parallel(
+        &mut self,
+        depth: u32,
+        tt: &Mutex<TranspositionTable>,
+        start_time: Instant,
+        timeout: Duration,
+        threads: usize,
+    ) -> Option<Move> {
+        let mut moves = self.generate_legal_moves();
+        if moves.is_empty() {
+            return None;
+        }
+
+        // 捕獲する手を先に試すと枝刈りが効きやすいので並べ替える
+        moves.sort_by_key(|m| !m.is_capture);
+
+        if let Some(entry) = tt.lock().unwrap().probe(self.zobrist()) {
+            if let Some(best) = entry.best_move {
+                if let Some(pos) = moves.iter().position(|&mv| mv == best) {
+                    moves.swap(0, pos);
+                }
+            }
+        }
+
+        let results: Mutex<Vec<(Move, i32)>> = Mutex::new(Vec::with_capacity(moves.len()));
+        let timed_out = AtomicBool::new(false);
+        let chunk_size = moves.len().div_ceil(threads).max(1);
+
+        // クロージャは`move`で取り込むしかないが、`results`/`timed_out`/`self`をそのまま
+        // moveすると最初のチャンクで取り込まれて以降のイテレーションで使えなくなる。
+        // そこで参照（Copy）をループの外で一度だけ束ねておき、各イテレーションでは
+        // その参照のコピーと`chunk`だけをmoveで取り込む
+        let results_ref = &results;
+        let timed_out_ref = &timed_out;
+        let board_ref: &Board = &*self;
+
+        crossbeam::thread::scope(|scope| {
+            for chunk in moves.chunks(chunk_size) {
+                scope.spawn(move |_| {
+                    // スレッドをまたいで盤面を共有できないので、このワーカーの持ち分の
+                    // 先頭でだけクローンし、以降はmake→再帰→unmakeで使い回す
+                    let mut board_copy = board_ref.clone();
+                    for &m in chunk {
+                        if start_time.elapsed() >= timeout {
+                            timed_out_ref.store(true, Ordering::Relaxed);
+                            return;
+                        }
+
+                        let undo = board_copy.make_move(m);
+                        let result = board_copy.minimax(depth - 1, -100001, 100001, tt, start_time, timeout);
+                        board_copy.unmake_move(m, undo);
+                        // 子局面は相手視点の評価値を返すので、符号を反転して自分視点に戻す
+                        match result {
+                            Some(eval) => results_ref.lock().unwrap().push((m, -eval)),
+                            None => timed_out_ref.store(true, Ordering::Relaxed),
+                        }
+                    }
+                });
+            }
+        })
+        .expect("worker thread panicked");
+
+        if timed_out.load(Ordering::Relaxed) {
+            return None;
+        }
+
+        let results = results.into_inner().unwrap();
+        let &(best_move, best_eval) = results.iter().max_by_key(|&&(_, eval)| eval)?;
+
+        tt.lock()
+            .unwrap()
+            .store(self.zobrist(), depth, best_eval, TTFlag::Exact, Some(best_move));
+
         Some(best_move)
     }
 
-    /// Min-Maxアルゴリズムで局面を評価する
+    /// 静止探索（Quiescence Search）
+    ///
+    /// `minimax`が深さ0に達した局面をそのまま`evaluate`するとホライズン効果（あと1手で
+    /// 駒を取られる/取れることが見えない）が起きるので、代わりにこれを呼ぶ。
+    /// 現局面の評価値を「指さない（stand-pat）」場合の下限として扱い、それ以上
+    /// 既にbetaを超えていれば打ち切る。そうでなければ捕獲する手だけを生成し、
+    /// MVV-LVA（安い駒で高い駒を取る手を優先）で並べ替えてから再帰し、
+    /// 捕り合いが尽きる（静かな局面になる）まで続ける
+    ///
+    /// # 引数
+    /// * `alpha` - 現在のアルファ値（手番側から見た下限）
+    /// * `beta` - 現在のベータ値（手番側から見た上限）
+    /// * `start_time` - 探索開始時刻
+    /// * `timeout` - 探索の制限時間
+    ///
+    /// # 戻り値
+    /// タイムアウト前に完了した場合は手番側から見た評価値、タイムアウトした場合はNone
+    fn quiescence(
+        &mut self,
+        mut alpha: i32,
+        beta: i32,
+        start_time: Instant,
+        timeout: Duration,
+    ) -> Option<i32> {
+        if start_time.elapsed() >= timeout {
+            return None;
+        }
+
+        let stand_pat = self.evaluate();
+        if stand_pat >= beta {
+            return Some(beta);
+        }
+        if stand_pat > alpha {
+            alpha = stand_pat;
+        }
+
+        let mut captures: Vec<Move> = self
+            .generate_legal_moves()
+            .into_iter()
+            .filter(|m| m.is_capture)
+            .collect();
+
+        // MVV-LVA: 捕られる駒の価値が高く、捕る駒の価値が低い手ほど先に試す
+        captures.sort_by_key(|m| {
+            let victim = self.piece_at(m.to).map(|p| evaluate::get_piece_value(p.kind)).unwrap_or(0);
+            let attacker = self.piece_at(m.from).map(|p| evaluate::get_piece_value(p.kind)).unwrap_or(0);
+            attacker - victim
+        });
+
+        for m in captures {
+            let undo = self.make_move(m);
+            let result = self.quiescence(-beta, -alpha, start_time, timeout);
+            self.unmake_move(m, undo);
+            let score = match result {
+                Some(s) => -s,
+                None => return None,
+            };
+
+            if score >= beta {
+                return Some(beta);
+            }
+            if score > alpha {
+                alpha = score;
+            }
+        }
+
+        Some(alpha)
+    }
+
+    /// アルファベータ枝刈りつきnegamaxアルゴリズムで局面を評価する
+    ///
+    /// 常に手番側から見た評価値（正なら手番側が有利）を返す単一の再帰関数。
+    /// 子局面の評価値は相手視点なので、符号を反転して自分視点に戻す
+    /// （`score = -minimax(depth - 1, -beta, -alpha, ...)`）。
+    /// `score >= beta` ならフェイルハードで`beta`を即座に返して打ち切り、
+    /// `score > alpha` なら`alpha`を引き上げて最善手を更新する。
+    /// 置換表を参照し、十分な深さで既に探索済みならその結果で枝刈りし、
+    /// そうでなければ置換表の最善手を先頭候補として探索する
     ///
     /// # 引数
     /// * `depth` - 探索深度
-    /// * `maximizing` - 最大化側（白）の手番かどうか
+    /// * `alpha` - 現在のアルファ値（手番側から見た下限）
+    /// * `beta` - 現在のベータ値（手番側から見た上限）
+    /// * `tt` - 置換表
     /// * `start_time` - 探索開始時刻
     /// * `timeout` - 探索の制限時間
     ///
     /// # 戻り値
-    /// タイムアウト前に完了した場合は評価値、タイムアウトした場合はNone
-    fn minimax(&self, depth: u32, maximizing: bool, start_time: Instant, timeout: Duration) -> Option<i32> {
+    /// タイムアウト前に完了した場合は手番側から見た評価値、タイムアウトした場合はNone
+    /// 静止探索（Quiescence Search）
+    ///
+    /// `minimax`が深さ0に達した局面をそのまま`evaluate`するとホライズン効果（あと1手で
+    /// 駒を取られる/取れることが見えない）が起きるので、代わりにこれを呼ぶ。
+    /// 現局面の評価値を「指さない（stand-pat）」場合の下限として扱い、それ以上
+    /// 既にbetaを超えていれば打ち切る。そうでなければ捕獲する手だけを生成し、
+    /// MVV-LVA（安い駒で高い駒を取る手を優先）で並べ替えてから再帰し、
+    /// 捕り合いが尽きる（静かな局面になる）まで続ける
+    ///
+    /// # 引数
+    /// * `alpha` - 現在のアルファ値（手番側から見た下限）
+    /// * `beta` - 現在のベータ値（手番側から見た上限）
+    /// * `start_time` - 探索開始時刻
+    /// * `timeout` - 探索の制限時間
+    ///
+    /// # 戻り値
+    /// タイムアウト前に完了した場合は手番側から見た評価値、タイムアウトした場合はNone
+    fn quiescence(
+        &mut self,
+        mut alpha: i32,
+        beta: i32,
+        start_time: Instant,
+        timeout: Duration,
+    ) -> Option<i32> {
+        if start_time.elapsed() >= timeout {
+            return None;
+        }
+
+        let stand_pat = self.evaluate();
+        if stand_pat >= beta {
+            return Some(beta);
+        }
+        if stand_pat > alpha {
+            alpha = stand_pat;
+        }
+
+        let mut captures: Vec<Move> = self
+            .generate_legal_moves()
+            .into_iter()
+            .filter(|m| m.is_capture)
+            .collect();
+
+        // MVV-LVA: 捕られる駒の価値が高く、捕る駒の価値が低い手ほど先に試す
+        captures.sort_by_key(|m| {
+            let victim = self.piece_at(m.to).map(|p| evaluate::get_piece_value(p.kind)).unwrap_or(0);
+            let attacker = self.piece_at(m.from).map(|p| evaluate::get_piece_value(p.kind)).unwrap_or(0);
+            attacker - victim
+        });
+
+        for m in captures {
+            let undo = self.make_move(m);
+            let result = self.quiescence(-beta, -alpha, start_time, timeout);
+            self.unmake_move(m, undo);
+            let score = match result {
+                Some(s) => -s,
+                None => return None,
+            };
+
+            if score >= beta {
+                return Some(beta);
+            }
+            if score > alpha {
+                alpha = score;
+            }
+        }
+
+        Some(alpha)
+    }
+
+    fn minimax(
+        &mut self,
+        depth: u32,
+        mut alpha: i32,
+        mut beta: i32,
+        tt: &Mutex<TranspositionTable>,
+        start_time: Instant,
+        timeout: Duration,
+    ) -> Option<i32> {
         // タイムアウトチェック
         if start_time.elapsed() >= timeout {
             return None;
         }
 
+        let key = self.zobrist();
+        // 一度だけ置換表を引き、カットオフ判定と後段の手の並べ替えの両方で使い回す
+        let tt_entry = tt.lock().unwrap().probe(key);
+
+        if let Some(entry) = tt_entry {
+            if entry.depth >= depth {
+                match entry.flag {
+                    TTFlag::Exact => return Some(entry.value),
+                    TTFlag::LowerBound => alpha = alpha.max(entry.value),
+                    TTFlag::UpperBound => beta = beta.min(entry.value),
+                }
+                if alpha >= beta {
+                    return Some(entry.value);
+                }
+            }
+        }
+
         if depth == 0 || self.is_game_over() {
             if self.is_checkmate() {
-                return Some(if maximizing { -100000 } else { 100000 });
+                // 手番側が詰まされている＝最悪の評価。残り深さが大きいほど
+                // （＝より少ない手数で詰んだほど）大きなペナルティにして、
+                // 短手数の詰みを優先的に選ばせる
+                return Some(-(100000 + depth as i32));
             }
-            if self.is_stalemate() {
+            if self.is_stalemate() || self.is_draw() {
+                // ステイルメイト・50手ルール・千日手はいずれも引き分けなので評価値0
                 return Some(0);
             }
-            return Some(self.evaluate());
+            // 駒得が曖昧な局面で評価を打ち切るとホライズン効果を起こすので、
+            // 捕り合いが収まるまで静止探索で延長してから評価する
+            return self.quiescence(alpha, beta, start_time, timeout);
         }
 
-        let moves = self.generate_legal_moves();
+        let mut moves = self.generate_legal_moves();
         if moves.is_empty() {
-            return Some(if maximizing { -100000 } else { 100000 });
+            return Some(-(100000 + depth as i32));
         }
 
-        if maximizing {
-            let mut max_eval = -100001;
-            for m in moves {
-                let mut board_copy = self.clone();
-                board_copy.make_move(m);
-                let eval = board_copy.minimax(depth - 1, false, start_time, timeout)?;
-                max_eval = max_eval.max(eval);
+        // 捕獲する手を先に試すと枝刈りが効きやすいので並べ替える
+        moves.sort_by_key(|m| !m.is_capture);
+
+        // 置換表の最善手を先頭候補にする。衝突でキーだけ一致し別局面の手が
+        // 入っている可能性があるので、現局面で実際に生成された合法手の中にあるかを
+        // 確認してから使う（無ければ無視してそのまま並び順を使う）
+        if let Some(entry) = tt_entry {
+            if let Some(best) = entry.best_move {
+                if let Some(pos) = moves.iter().position(|&mv| mv == best) {
+                    moves.swap(0, pos);
+                }
             }
-            Some(max_eval)
-        } else {
-            let mut min_eval = 100001;
-            for m in moves {
-                let mut board_copy = self.clone();
-                board_copy.make_move(m);
-                let eval = board_copy.minimax(depth - 1, true, start_time, timeout)?;
-                min_eval = min_eval.min(eval);
+        }
+
+        let original_alpha = alpha;
+        let mut best_move: Option<Move> = None;
+
+        for m in moves {
+            // 盤面をクローンせず、make→再帰→unmakeで同じ盤面を使い回す。
+            // タイムアウト（None）の場合も必ずunmakeしてから伝播させ、
+            // 巻き戻し漏れで盤面が壊れたまま呼び出し元へ返らないようにする
+            let undo = self.make_move(m);
+            let result = self.minimax(depth - 1, -beta, -alpha, tt, start_time, timeout);
+            self.unmake_move(m, undo);
+            let score = match result {
+                Some(s) => -s,
+                None => return None,
+            };
+
+            if score >= beta {
+                // フェイルハード: betaをそのまま返して打ち切る
+                tt.lock().unwrap().store(key, depth, beta, TTFlag::LowerBound, Some(m));
+                return Some(beta);
+            }
+            if score > alpha {
+                alpha = score;
+                best_move = Some(m);
             }
-            Some(min_eval)
         }
+
+        let flag = if alpha <= original_alpha {
+            TTFlag::UpperBound
+        } else {
+            TTFlag::Exact
+        };
+        tt.lock().unwrap().store(key, depth, alpha, flag, best_move);
+
+        Some(alpha)
     }
 
     /// 盤面状態を正規化された文字列に変換する
@@ -1391,16 +2725,46 @@ impl Board {
 
     /// 指し手をSAN（標準代数記法）形式の文字列に変換する
     ///
+    /// 手を指した結果チェックになる場合は'+'、チェックメイトになる場合は'#'を末尾に付ける
+    ///
     /// # 引数
     /// * `m` - 変換する手
-    pub fn move_to_san(&self, m: Move) -> String {
-        if m.is_castle_kingside {
-            return "O-O".to_string();
-        }
-        if m.is_castle_queenside {
-            return "O-O-O".to_string();
-        }
+    pub fn move_to_san(&mut self, m: Move) -> String {
+        let mut san = if m.is_castle_kingside {
+            "O-O".to_string()
+        } else if m.is_castle_queenside {
+            "O-O-O".to_string()
+        } else {
+            self.move_to_san_body(m)
+        };
+
+        san.push_str(self.check_or_mate_suffix(m));
+        san
+    }
+
+    /// 手を指した後に相手がチェック/チェックメイトになっているかを見て、
+    /// SANの末尾に付ける記号（'+'、'#'、または付けないなら空文字列）を返す
+    fn check_or_mate_suffix(&mut self, m: Move) -> &'static str {
+        let undo = self.make_move(m);
+        let king_square = self.find_king(self.side);
+        let in_check = king_square
+            .map(|sq| self.is_square_attacked(sq, Board::other(self.side)))
+            .unwrap_or(false);
+        let suffix = if in_check {
+            if self.generate_legal_moves().is_empty() {
+                "#"
+            } else {
+                "+"
+            }
+        } else {
+            ""
+        };
+        self.unmake_move(m, undo);
+        suffix
+    }
 
+    /// キャスリング以外の手をSAN本体（'+'/'#'を除く部分）に変換する
+    fn move_to_san_body(&mut self, m: Move) -> String {
         let piece = self.piece_at(m.from).unwrap();
         let mut san = String::new();
 
@@ -1465,6 +2829,29 @@ impl Board {
         san
     }
 
+    /// 指し手をUCI/ロングアルジェブライック形式（例: "e2e4", "e7e8q"）に変換する
+    ///
+    /// キャスリングも含めてキングの移動元・移動先で表現する（UCIプロトコルの慣習どおり）
+    pub fn move_to_uci(&self, m: Move) -> String {
+        let square_to_str = |i: usize| {
+            let f = (b'a' + file_of(i) as u8) as char;
+            let r = (b'1' + rank_of(i) as u8) as char;
+            format!("{}{}", f, r)
+        };
+
+        let mut s = format!("{}{}", square_to_str(m.from), square_to_str(m.to));
+        if let Some(promo) = m.promo {
+            s.push(match promo {
+                Kind::Queen => 'q',
+                Kind::Rook => 'r',
+                Kind::Bishop => 'b',
+                Kind::Knight => 'n',
+                _ => unreachable!(),
+            });
+        }
+        s
+    }
+
     /// 2つのマス間の経路が空かチェックする
     ///
     /// 長距離駒（ビショップ、ルーク、クイーン）の移動可否判定に使用
@@ -1613,3 +3000,200 @@ fn parse_square(s: &str) -> Result<usize, String> {
     }
     Ok(idx((b[0] - b'a') as usize, (b[1] - b'1') as usize))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `make_move` → `unmake_move` の往復で盤面が完全に元に戻ることを確認する
+    ///
+    /// 通常の手・アンパッサン・昇格・キャスリングそれぞれで、FENとZobristハッシュが
+    /// 往復の前後で一致することをチェックする
+    #[test]
+    fn unmake_move_restores_state_after_normal_move() {
+        let mut board = Board::new();
+        let before_fen = board.to_fen();
+        let before_zobrist = board.zobrist();
+
+        let mv = board.parse_san("e4").unwrap();
+        let undo = board.make_move(mv);
+        board.unmake_move(mv, undo);
+
+        assert_eq!(board.to_fen(), before_fen);
+        assert_eq!(board.zobrist(), before_zobrist);
+    }
+
+    #[test]
+    fn unmake_move_restores_state_after_en_passant() {
+        let mut board =
+            Board::from_fen("rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3").unwrap();
+        let before_fen = board.to_fen();
+        let before_zobrist = board.zobrist();
+
+        let mv = board.parse_san("exd6").unwrap();
+        assert!(mv.is_en_passant);
+        let undo = board.make_move(mv);
+        board.unmake_move(mv, undo);
+
+        assert_eq!(board.to_fen(), before_fen);
+        assert_eq!(board.zobrist(), before_zobrist);
+    }
+
+    #[test]
+    fn unmake_move_restores_state_after_promotion() {
+        let mut board = Board::from_fen("8/P7/8/8/8/8/8/k6K w - - 0 1").unwrap();
+        let before_fen = board.to_fen();
+        let before_zobrist = board.zobrist();
+
+        let mv = board.parse_san("a8=Q").unwrap();
+        assert_eq!(mv.promo, Some(Kind::Queen));
+        let undo = board.make_move(mv);
+        board.unmake_move(mv, undo);
+
+        assert_eq!(board.to_fen(), before_fen);
+        assert_eq!(board.zobrist(), before_zobrist);
+    }
+
+    #[test]
+    fn unmake_move_restores_state_after_castling() {
+        let mut board = Board::new();
+        for tok in ["e4", "e5", "Nf3", "Nc6", "Bc4", "Bc5"] {
+            board.parse_and_play_token(tok).unwrap();
+        }
+        let before_fen = board.to_fen();
+        let before_zobrist = board.zobrist();
+
+        let mv = board.parse_san("O-O").unwrap();
+        assert!(mv.is_castle_kingside);
+        let undo = board.make_move(mv);
+        board.unmake_move(mv, undo);
+
+        assert_eq!(board.to_fen(), before_fen);
+        assert_eq!(board.zobrist(), before_zobrist);
+    }
+
+    /// Chess960のキャスリングは、キング・ルークの開始ファイルが標準と異なっていても
+    /// キングがg/cファイル、ルークがf/dファイルへ移動することを確認する
+    #[test]
+    fn chess960_castling_moves_to_standard_destination_squares() {
+        // キングd1/d8、ルークa1・h1/a8・h8というe/h以外の開始ファイルの局面
+        let mut board =
+            Board::from_fen("r2k3r/pppppppp/8/8/8/8/PPPPPPPP/R2K3R w HAha - 0 1").unwrap();
+
+        let moves = board.generate_legal_moves();
+        assert!(moves.iter().any(|m| m.is_castle_kingside));
+        assert!(moves.iter().any(|m| m.is_castle_queenside));
+
+        let kingside = *moves.iter().find(|m| m.is_castle_kingside).unwrap();
+        let undo = board.make_move(kingside);
+        assert_eq!(board.piece_at(parse_square("g1").unwrap()).map(|p| p.kind), Some(Kind::King));
+        assert_eq!(board.piece_at(parse_square("f1").unwrap()).map(|p| p.kind), Some(Kind::Rook));
+        board.unmake_move(kingside, undo);
+
+        let before_fen = board.to_fen();
+        let before_zobrist = board.zobrist();
+        let queenside = *board.generate_legal_moves().iter().find(|m| m.is_castle_queenside).unwrap();
+        let undo = board.make_move(queenside);
+        assert_eq!(board.piece_at(parse_square("c1").unwrap()).map(|p| p.kind), Some(Kind::King));
+        assert_eq!(board.piece_at(parse_square("d1").unwrap()).map(|p| p.kind), Some(Kind::Rook));
+        board.unmake_move(queenside, undo);
+        assert_eq!(board.to_fen(), before_fen);
+        assert_eq!(board.zobrist(), before_zobrist);
+    }
+
+    /// `from_chess960_id`はWikipediaの"Chess960 numbering scheme"に従う
+    /// （ID 518は標準チェスの初期配置と一致する）
+    #[test]
+    fn from_chess960_id_518_is_standard_chess() {
+        let board = Board::from_chess960_id(518);
+        assert_eq!(
+            board.to_fen(),
+            Board::new().to_fen(),
+            "id 518 should reproduce the standard starting position"
+        );
+    }
+
+    /// 任意のID（非標準の並び）でも、back rankは両端がルーク、中央にキングが
+    /// 挟まれる有効な配置になる
+    #[test]
+    fn from_chess960_id_produces_valid_back_rank_for_shuffled_ids() {
+        for id in [0u16, 1, 959] {
+            let board = Board::from_chess960_id(id);
+            assert!(board.rook_start_file_q < board.king_start_file);
+            assert!(board.king_start_file < board.rook_start_file_k);
+            assert_eq!(board.piece_at(idx(board.king_start_file, 0)).map(|p| p.kind), Some(Kind::King));
+            assert_eq!(board.piece_at(idx(board.rook_start_file_k, 0)).map(|p| p.kind), Some(Kind::Rook));
+            assert_eq!(board.piece_at(idx(board.rook_start_file_q, 0)).map(|p| p.kind), Some(Kind::Rook));
+        }
+    }
+
+    /// 最終段に到達するポーンの手は昇格先の指定が必須で、それ以外の手では指定できない
+    #[test]
+    fn promotion_piece_required_exactly_on_last_rank_pawn_moves() {
+        let board = Board::from_fen("8/P7/8/8/8/8/8/k6K w - - 0 1").unwrap();
+
+        // 最終段到達なのに昇格先なし -> エラー
+        assert!(board.try_parse_uci_like("a7a8").is_err());
+
+        // 最終段到達かつ昇格先あり -> 成功
+        let mv = board.try_parse_uci_like("a7a8q").unwrap().unwrap();
+        assert_eq!(mv.promo, Some(Kind::Queen));
+
+        // 最終段に到達しないのに昇格先を指定 -> エラー
+        let board = Board::new();
+        assert!(board.try_parse_uci_like("a2a3q").is_err());
+    }
+
+    /// `move_to_san`はチェック・メイトの手に"+"/"#"を付けることを確認する
+    #[test]
+    fn move_to_san_appends_check_and_mate_suffixes() {
+        // フールズメイト: 1. f3 e5 2. g4 Qh4#
+        let mut board = Board::new();
+        for tok in ["f3", "e5", "g4"] {
+            board.parse_and_play_token(tok).unwrap();
+        }
+        let mv = board.parse_san("Qh4").unwrap();
+        assert_eq!(board.move_to_san(mv), "Qh4#");
+
+        // メイトではない王手: 1. e4 e5 2. Qh5 Nc6 3. Qxf7+
+        // （ビショップがまだc4に出ていないためf7のクイーンは無防備で、Kxf7で取り返せる）
+        let mut board = Board::new();
+        for tok in ["e4", "e5", "Qh5", "Nc6"] {
+            board.parse_and_play_token(tok).unwrap();
+        }
+        let mv = board.parse_san("Qxf7").unwrap();
+        assert_eq!(board.move_to_san(mv), "Qxf7+");
+    }
+
+    /// `parse_san`がキャスリングを読み取り、`move_to_san`で同じ表記に戻ることを確認する
+    #[test]
+    fn parse_san_round_trips_castling() {
+        let mut board = Board::new();
+        for tok in ["e4", "e5", "Nf3", "Nc6", "Bc4", "Bc5"] {
+            board.parse_and_play_token(tok).unwrap();
+        }
+        let mv = board.parse_san("O-O").unwrap();
+        assert!(mv.is_castle_kingside);
+        assert_eq!(board.move_to_san(mv), "O-O");
+    }
+
+    /// Shredder-FENで片方の色のキャスリング権しか残っていない場合でも、その色の
+    /// ルーク開始ファイルを正しく読み取ること（白が両方のキャスリング権を失った後の
+    /// 局面を想定）
+    #[test]
+    fn from_fen_shredder_reads_rook_file_from_black_only_rights() {
+        let board =
+            Board::from_fen("nrbkqrbn/pppppppp/8/8/8/8/PPPPPPPP/NRBKQRBN w fb - 0 1").unwrap();
+
+        assert_eq!(board.king_start_file, 3);
+        assert_eq!(board.rook_start_file_k, 5);
+        assert_eq!(board.rook_start_file_q, 1);
+    }
+
+    #[test]
+    fn from_fen_shredder_rejects_mismatched_king_files() {
+        // 白キングe1・黒キングg8という、この盤面表現では表せない非対称な配置
+        let result = Board::from_fen("6k1/8/8/8/8/8/8/4K3 w H - 0 1");
+        assert!(result.is_err());
+    }
+}
@@ -16,10 +16,10 @@ impl Cache {
         }
     }
 
-    /// 盤面状態、タイムアウト、スレッド数、評価関数からキャッシュキー（ハッシュ値）を生成する
-    fn generate_key(&self, board_state: &str, timeout_secs: u64, threads: Option<usize>, evaluator: crate::EvaluatorType) -> String {
+    /// 盤面のZobristハッシュ、タイムアウト、スレッド数、評価関数からキャッシュキー（ハッシュ値）を生成する
+    fn generate_key(&self, zobrist: u64, timeout_secs: u64, threads: Option<usize>, evaluator: crate::EvaluatorType) -> String {
         let mut hasher = Sha256::new();
-        hasher.update(board_state.as_bytes());
+        hasher.update(zobrist.to_le_bytes());
         hasher.update(b"timeout:");
         hasher.update(timeout_secs.to_string().as_bytes());
         hasher.update(b"threads:");
@@ -37,12 +37,12 @@ impl Cache {
     /// キャッシュから最善手を読み込む
     ///
     /// # 引数
-    /// * `board_state` - 正規化された盤面状態の文字列
+    /// * `zobrist` - 盤面のZobristハッシュ
     /// * `timeout_secs` - タイムアウト（秒単位）
     /// * `threads` - スレッド数（Noneの場合は直列実行）
     /// * `evaluator` - 評価関数の種類
-    pub fn read(&self, board_state: &str, timeout_secs: u64, threads: Option<usize>, evaluator: crate::EvaluatorType) -> Option<String> {
-        let key = self.generate_key(board_state, timeout_secs, threads, evaluator);
+    pub fn read(&self, zobrist: u64, timeout_secs: u64, threads: Option<usize>, evaluator: crate::EvaluatorType) -> Option<String> {
+        let key = self.generate_key(zobrist, timeout_secs, threads, evaluator);
         let path = self.get_path(&key);
 
         if !path.exists() {
@@ -57,15 +57,15 @@ impl Cache {
     /// キャッシュに最善手を書き込む
     ///
     /// # 引数
-    /// * `board_state` - 正規化された盤面状態の文字列
+    /// * `zobrist` - 盤面のZobristハッシュ
     /// * `timeout_secs` - タイムアウト（秒単位）
     /// * `threads` - スレッド数（Noneの場合は直列実行）
     /// * `evaluator` - 評価関数の種類
     /// * `best_move` - 最善手（SAN形式）
-    pub fn write(&self, board_state: &str, timeout_secs: u64, threads: Option<usize>, evaluator: crate::EvaluatorType, best_move: &str) -> std::io::Result<()> {
+    pub fn write(&self, zobrist: u64, timeout_secs: u64, threads: Option<usize>, evaluator: crate::EvaluatorType, best_move: &str) -> std::io::Result<()> {
         fs::create_dir_all(&self.cache_dir)?;
 
-        let key = self.generate_key(board_state, timeout_secs, threads, evaluator);
+        let key = self.generate_key(zobrist, timeout_secs, threads, evaluator);
         let path = self.get_path(&key);
 
         let result = serde_json::json!({
@@ -3,6 +3,7 @@
 /// Piece-Square Tablesと追加ボーナスを使用して局面を評価する
 use crate::board::{Board, Color, Kind, Piece};
 use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::{Mutex, OnceLock};
 
 /// 評価関数の種類を保持するグローバル変数
 static EVALUATOR_TYPE: AtomicU8 = AtomicU8::new(0); // 0 = Advanced, 1 = Classic
@@ -23,42 +24,82 @@ const ROOK_VALUE: i32 = 500;
 const QUEEN_VALUE: i32 = 900;
 const KING_VALUE: i32 = 20000;
 
-/// Piece-Square Tables: ポーン
+/// Piece-Square Tables: ポーン（中盤）
 /// 白視点での評価値（黒の場合は上下反転）
-const PAWN_TABLE: [i32; 64] = [
+const PAWN_MG_TABLE: [i32; 64] = [
     0, 0, 0, 0, 0, 0, 0, 0, 50, 50, 50, 50, 50, 50, 50, 50, 10, 10, 20, 30, 30, 20, 10, 10, 5, 5,
     10, 25, 25, 10, 5, 5, 0, 0, 0, 20, 20, 0, 0, 0, 5, -5, -10, 0, 0, -10, -5, 5, 5, 10, 10, -20,
     -20, 10, 10, 5, 0, 0, 0, 0, 0, 0, 0, 0,
 ];
 
-/// Piece-Square Tables: ナイト
-const KNIGHT_TABLE: [i32; 64] = [
+/// Piece-Square Tables: ポーン（終盤）
+/// 終盤はパスポーンの駆け込みを重視し、ランクが上がるほど評価を上げる
+/// （添字0=a1。先頭の8要素が1段目）
+const PAWN_EG_TABLE: [i32; 64] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 10, 10, 10, 10, 10, 10, 10, 10, 10, 10, 10, 10, 10, 10, 10, 10, 20, 20,
+    20, 20, 20, 20, 20, 20, 30, 30, 30, 30, 30, 30, 30, 30, 50, 50, 50, 50, 50, 50, 50, 50, 80, 80,
+    80, 80, 80, 80, 80, 80, 0, 0, 0, 0, 0, 0, 0, 0,
+];
+
+/// Piece-Square Tables: ナイト（中盤）
+const KNIGHT_MG_TABLE: [i32; 64] = [
     -50, -40, -30, -30, -30, -30, -40, -50, -40, -20, 0, 0, 0, 0, -20, -40, -30, 0, 10, 15, 15, 10,
     0, -30, -30, 5, 15, 20, 20, 15, 5, -30, -30, 0, 15, 20, 20, 15, 0, -30, -30, 5, 10, 15, 15, 10,
     5, -30, -40, -20, 0, 5, 5, 0, -20, -40, -50, -40, -30, -30, -30, -30, -40, -50,
 ];
 
-/// Piece-Square Tables: ビショップ
-const BISHOP_TABLE: [i32; 64] = [
+/// Piece-Square Tables: ナイト（終盤）
+/// 終盤はナイトの駒単独の価値が下がるため中盤より平坦にする
+const KNIGHT_EG_TABLE: [i32; 64] = [
+    -40, -30, -20, -20, -20, -20, -30, -40, -30, -10, 0, 0, 0, 0, -10, -30, -20, 0, 5, 10, 10, 5,
+    0, -20, -20, 0, 10, 15, 15, 10, 0, -20, -20, 0, 10, 15, 15, 10, 0, -20, -20, 0, 5, 10, 10, 5, 0,
+    -20, -30, -10, 0, 0, 0, 0, -10, -30, -40, -30, -20, -20, -20, -20, -30, -40,
+];
+
+/// Piece-Square Tables: ビショップ（中盤）
+const BISHOP_MG_TABLE: [i32; 64] = [
     -20, -10, -10, -10, -10, -10, -10, -20, -10, 0, 0, 0, 0, 0, 0, -10, -10, 0, 5, 10, 10, 5, 0,
     -10, -10, 5, 5, 10, 10, 5, 5, -10, -10, 0, 10, 10, 10, 10, 0, -10, -10, 10, 10, 10, 10, 10, 10,
     -10, -10, 5, 0, 0, 0, 0, 5, -10, -20, -10, -10, -10, -10, -10, -10, -20,
 ];
 
-/// Piece-Square Tables: ルーク
-const ROOK_TABLE: [i32; 64] = [
+/// Piece-Square Tables: ビショップ（終盤）
+const BISHOP_EG_TABLE: [i32; 64] = [
+    -10, -5, -5, -5, -5, -5, -5, -10, -5, 0, 0, 0, 0, 0, 0, -5, -5, 0, 5, 5, 5, 5, 0, -5, -5, 0, 5,
+    10, 10, 5, 0, -5, -5, 0, 5, 10, 10, 5, 0, -5, -5, 0, 5, 5, 5, 5, 0, -5, -5, 0, 0, 0, 0, 0, 0,
+    -5, -10, -5, -5, -5, -5, -5, -5, -10,
+];
+
+/// Piece-Square Tables: ルーク（中盤）
+const ROOK_MG_TABLE: [i32; 64] = [
     0, 0, 0, 0, 0, 0, 0, 0, 5, 10, 10, 10, 10, 10, 10, 5, -5, 0, 0, 0, 0, 0, 0, -5, -5, 0, 0, 0, 0,
     0, 0, -5, -5, 0, 0, 0, 0, 0, 0, -5, -5, 0, 0, 0, 0, 0, 0, -5, -5, 0, 0, 0, 0, 0, 0, -5, 0, 0,
     0, 5, 5, 0, 0, 0,
 ];
 
-/// Piece-Square Tables: クイーン
-const QUEEN_TABLE: [i32; 64] = [
+/// Piece-Square Tables: ルーク（終盤）
+/// 終盤は7段目（相手陣地）のルークを重視する（添字0=a1。先頭の8要素が1段目）
+const ROOK_EG_TABLE: [i32; 64] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 10, 10, 10, 10, 10, 10, 10, 10, 0, 0,
+    0, 0, 0, 0,
+];
+
+/// Piece-Square Tables: クイーン（中盤）
+const QUEEN_MG_TABLE: [i32; 64] = [
     -20, -10, -10, -5, -5, -10, -10, -20, -10, 0, 0, 0, 0, 0, 0, -10, -10, 0, 5, 5, 5, 5, 0, -10,
     -5, 0, 5, 5, 5, 5, 0, -5, 0, 0, 5, 5, 5, 5, 0, -5, -10, 5, 5, 5, 5, 5, 0, -10, -10, 0, 5, 0, 0,
     0, 0, -10, -20, -10, -10, -5, -5, -10, -10, -20,
 ];
 
+/// Piece-Square Tables: クイーン（終盤）
+/// 終盤はクイーンを中央へ活性化させる
+const QUEEN_EG_TABLE: [i32; 64] = [
+    -10, -5, -5, -5, -5, -5, -5, -10, -5, 0, 0, 0, 0, 0, 0, -5, -5, 0, 5, 5, 5, 5, 0, -5, -5, 0, 5,
+    10, 10, 5, 0, -5, -5, 0, 5, 10, 10, 5, 0, -5, -5, 0, 5, 5, 5, 5, 0, -5, -5, 0, 0, 0, 0, 0, 0,
+    -5, -10, -5, -5, -5, -5, -5, -5, -10,
+];
+
 /// Piece-Square Tables: キング（中盤）
 /// キャスリングを推奨する配置
 const KING_MIDDLEGAME_TABLE: [i32; 64] = [
@@ -77,12 +118,209 @@ const KING_ENDGAME_TABLE: [i32; 64] = [
     -30, -50,
 ];
 
+/// ゲームフェーズ計算用の駒ごとの重み（開始局面の合計が24になるよう正規化）
+const PHASE_WEIGHT_KNIGHT: i32 = 1;
+const PHASE_WEIGHT_BISHOP: i32 = 1;
+const PHASE_WEIGHT_ROOK: i32 = 2;
+const PHASE_WEIGHT_QUEEN: i32 = 4;
+
+/// ゲームフェーズの最大値（開始局面相当。ナイト*4 + ビショップ*4 + ルーク*4 + クイーン*2 = 24）
+const MAX_PHASE: i32 = 24;
+
+/// 駒の種類からゲームフェーズへの寄与度を取得する
+fn phase_weight(kind: Kind) -> i32 {
+    match kind {
+        Kind::Knight => PHASE_WEIGHT_KNIGHT,
+        Kind::Bishop => PHASE_WEIGHT_BISHOP,
+        Kind::Rook => PHASE_WEIGHT_ROOK,
+        Kind::Queen => PHASE_WEIGHT_QUEEN,
+        Kind::Pawn | Kind::King => 0,
+    }
+}
+
 /// ボーナス: ビショップペア
 const BISHOP_PAIR_BONUS: i32 = 50;
 
 /// ボーナス: キャスリング権
 const CASTLING_RIGHTS_BONUS: i32 = 15;
 
+/// モビリティ（利かせているマスの数）1マスあたりのボーナス
+const MOBILITY_BONUS: i32 = 2;
+
+/// ペナルティ: 孤立ポーン（隣接ファイルに味方ポーンがいない）
+const ISOLATED_PAWN_PENALTY: i32 = 15;
+
+/// ペナルティ: 二重ポーン（同じファイルに味方ポーンが複数ある）
+const DOUBLED_PAWN_PENALTY: i32 = 10;
+
+/// ボーナス: パスポーン（同じファイルおよび隣接ファイルの前方に敵ポーンがいない）
+/// 添字はゴールまでの残りランク数ではなく、自陣から見た前進度（0=最初の段）。
+/// ゴールに近づくほど急激に価値が上がる
+const PASSED_PAWN_BONUS: [i32; 8] = [0, 5, 10, 20, 35, 60, 100, 0];
+
+/// ボーナス: オープンファイル（どちらの色のポーンも無い）上のルーク
+const ROOK_OPEN_FILE_BONUS: i32 = 20;
+
+/// ボーナス: セミオープンファイル（自分のポーンは無いが敵のポーンはある）上のルーク
+const ROOK_SEMI_OPEN_FILE_BONUS: i32 = 10;
+
+/// ポーン構造の評価結果
+///
+/// ポーン自体のスコアに加えて、ルークのオープン/セミオープンファイルボーナスの
+/// 判定にも使うファイル占有状況（ビット0=aファイル…ビット7=hファイル）を保持する
+#[derive(Clone, Copy)]
+struct PawnStructure {
+    /// 白視点のポーン構造スコア（パスポーン・孤立ポーン・二重ポーンの合計）
+    score: i32,
+    white_files: u8,
+    black_files: u8,
+}
+
+/// ポーンハッシュキャッシュのエントリ数
+const PAWN_HASH_SIZE: usize = 1 << 14;
+
+/// ポーン構造の評価結果をポーンのみのZobristサブキーでキャッシュするテーブル
+///
+/// ポーン構造は駒の動き全体のうちポーンの移動・捕獲でしか変化しないため、
+/// 探索中の多くの子局面で同じポーン配置を使い回せる（Stockfishのpawn hashと同じ発想）
+struct PawnHashTable {
+    entries: Vec<Option<(u64, PawnStructure)>>,
+}
+
+impl PawnHashTable {
+    fn new() -> Self {
+        PawnHashTable {
+            entries: vec![None; PAWN_HASH_SIZE],
+        }
+    }
+
+    fn index(&self, key: u64) -> usize {
+        (key as usize) % self.entries.len()
+    }
+
+    fn probe(&self, key: u64) -> Option<PawnStructure> {
+        let i = self.index(key);
+        self.entries[i].filter(|&(k, _)| k == key).map(|(_, s)| s)
+    }
+
+    fn store(&mut self, key: u64, structure: PawnStructure) {
+        let i = self.index(key);
+        self.entries[i] = Some((key, structure));
+    }
+}
+
+/// ポーンハッシュテーブルを取得する（初回呼び出し時に一度だけ確保し、以後は全探索・全対局で共有する）
+fn pawn_hash_table() -> &'static Mutex<PawnHashTable> {
+    static TABLE: OnceLock<Mutex<PawnHashTable>> = OnceLock::new();
+    TABLE.get_or_init(|| Mutex::new(PawnHashTable::new()))
+}
+
+/// 指定ファイル上の全マスを表すビットボード（0=aファイル）
+fn file_mask(file: usize) -> u64 {
+    0x0101_0101_0101_0101u64 << file
+}
+
+/// 指定ファイルに隣接するファイル（左右）のビットボード
+fn adjacent_files_mask(file: usize) -> u64 {
+    let mut m = 0u64;
+    if file > 0 {
+        m |= file_mask(file - 1);
+    }
+    if file < 7 {
+        m |= file_mask(file + 1);
+    }
+    m
+}
+
+/// `color`が進む向きで`rank`より前方にある全ランクのビットボード
+fn forward_ranks_mask(rank: usize, color: Color) -> u64 {
+    let mut m = 0u64;
+    match color {
+        Color::White => {
+            for r in (rank + 1)..8 {
+                m |= 0xFFu64 << (r * 8);
+            }
+        }
+        Color::Black => {
+            for r in 0..rank {
+                m |= 0xFFu64 << (r * 8);
+            }
+        }
+    }
+    m
+}
+
+/// ポーン構造（パスポーン・孤立ポーン・二重ポーン・ファイル占有）を計算する
+///
+/// キャッシュを経由せず毎回計算する生の実装。呼び出し側の[`pawn_structure`]が
+/// ポーンのみのZobristキーでこの結果をキャッシュする
+fn compute_pawn_structure(board: &Board) -> PawnStructure {
+    let white_pawns = board.pawn_bitboard(Color::White);
+    let black_pawns = board.pawn_bitboard(Color::Black);
+
+    let mut white_files = 0u8;
+    let mut bb = white_pawns;
+    while bb != 0 {
+        white_files |= 1 << (bb.trailing_zeros() as usize % 8);
+        bb &= bb - 1;
+    }
+    let mut black_files = 0u8;
+    let mut bb = black_pawns;
+    while bb != 0 {
+        black_files |= 1 << (bb.trailing_zeros() as usize % 8);
+        bb &= bb - 1;
+    }
+
+    let mut score = 0;
+    for color in [Color::White, Color::Black] {
+        let (own, enemy) = match color {
+            Color::White => (white_pawns, black_pawns),
+            Color::Black => (black_pawns, white_pawns),
+        };
+        let sign = if color == Color::White { 1 } else { -1 };
+
+        let mut bb = own;
+        while bb != 0 {
+            let sq = bb.trailing_zeros() as usize;
+            let file = sq % 8;
+            let rank = sq / 8;
+
+            if (own & file_mask(file)).count_ones() > 1 {
+                score -= sign * DOUBLED_PAWN_PENALTY;
+            }
+
+            if own & adjacent_files_mask(file) == 0 {
+                score -= sign * ISOLATED_PAWN_PENALTY;
+            }
+
+            let ahead_mask = (file_mask(file) | adjacent_files_mask(file)) & forward_ranks_mask(rank, color);
+            if enemy & ahead_mask == 0 {
+                let progress = if color == Color::White { rank } else { 7 - rank };
+                score += sign * PASSED_PAWN_BONUS[progress];
+            }
+
+            bb &= bb - 1;
+        }
+    }
+
+    PawnStructure {
+        score,
+        white_files,
+        black_files,
+    }
+}
+
+/// ポーン構造の評価結果を取得する（ポーンのみのZobristサブキーでキャッシュ済みなら再利用する）
+fn pawn_structure(board: &Board) -> PawnStructure {
+    let key = board.pawn_zobrist();
+    if let Some(cached) = pawn_hash_table().lock().unwrap().probe(key) {
+        return cached;
+    }
+    let structure = compute_pawn_structure(board);
+    pawn_hash_table().lock().unwrap().store(key, structure);
+    structure
+}
+
 /// クラシック評価関数: 駒の価値の合計のみで評価
 ///
 /// 以前の単純な評価関数。位置評価を行わず、駒の価値のみで評価する。
@@ -132,7 +370,10 @@ pub fn evaluate(board: &Board) -> i32 {
     }
 }
 
-/// 高度な評価関数: Piece-Square Tables使用
+/// 高度な評価関数: テーパード評価（中盤/終盤のPiece-Square Tablesを補間）
+///
+/// 盤上の駒構成からゲームフェーズを算出し、中盤用と終盤用の評価値を
+/// `(mg * phase + eg * (MAX_PHASE - phase)) / MAX_PHASE` で滑らかに混ぜる
 ///
 /// # 引数
 /// * `board` - 評価する盤面
@@ -140,64 +381,106 @@ pub fn evaluate(board: &Board) -> i32 {
 /// # 戻り値
 /// 評価値（白から見て正の値が有利、負の値が不利）
 fn evaluate_advanced(board: &Board) -> i32 {
-    let mut score = 0;
-
-    // 駒の数をカウント（終盤判定用）
-    let mut piece_count = 0;
+    let mut mg_score = 0;
+    let mut eg_score = 0;
+    let mut phase = 0;
     let mut white_bishops = 0;
     let mut black_bishops = 0;
 
+    let pawns = pawn_structure(board);
+
     // 各マスの駒を評価
     for i in 0..64 {
         if let Some(piece) = board.piece_at(i) {
-            piece_count += 1;
+            phase += phase_weight(piece.kind);
 
-            // 駒の基本価値
             let material_value = get_piece_value(piece.kind);
+            let (mg_positional, eg_positional) = get_positional_values(piece, i);
 
-            // 位置評価
-            let positional_value = get_positional_value(piece, i, piece_count <= 14);
-
-            let total_value = material_value + positional_value;
+            let mg_value = material_value + mg_positional;
+            let eg_value = material_value + eg_positional;
 
             match piece.color {
                 Color::White => {
-                    score += total_value;
+                    mg_score += mg_value;
+                    eg_score += eg_value;
                     if piece.kind == Kind::Bishop {
                         white_bishops += 1;
                     }
                 }
                 Color::Black => {
-                    score -= total_value;
+                    mg_score -= mg_value;
+                    eg_score -= eg_value;
                     if piece.kind == Kind::Bishop {
                         black_bishops += 1;
                     }
                 }
             }
+
+            // ルークのオープン/セミオープンファイルボーナス
+            if piece.kind == Kind::Rook {
+                let file_bit = 1u8 << (i % 8);
+                let (own_files, enemy_files) = match piece.color {
+                    Color::White => (pawns.white_files, pawns.black_files),
+                    Color::Black => (pawns.black_files, pawns.white_files),
+                };
+                if own_files & file_bit == 0 {
+                    let bonus = if enemy_files & file_bit == 0 {
+                        ROOK_OPEN_FILE_BONUS
+                    } else {
+                        ROOK_SEMI_OPEN_FILE_BONUS
+                    };
+                    match piece.color {
+                        Color::White => {
+                            mg_score += bonus;
+                            eg_score += bonus;
+                        }
+                        Color::Black => {
+                            mg_score -= bonus;
+                            eg_score -= bonus;
+                        }
+                    }
+                }
+            }
         }
     }
 
-    // ビショップペアボーナス
+    // ポーン構造（パスポーン・孤立ポーン・二重ポーン）
+    mg_score += pawns.score;
+    eg_score += pawns.score;
+
+    // ビショップペアボーナス（中盤・終盤とも同じ値を使う）
     if white_bishops >= 2 {
-        score += BISHOP_PAIR_BONUS;
+        mg_score += BISHOP_PAIR_BONUS;
+        eg_score += BISHOP_PAIR_BONUS;
     }
     if black_bishops >= 2 {
-        score -= BISHOP_PAIR_BONUS;
+        mg_score -= BISHOP_PAIR_BONUS;
+        eg_score -= BISHOP_PAIR_BONUS;
     }
 
-    // キャスリング権ボーナス
+    // キャスリング権ボーナス（終盤に入るとキャスリングの価値は下がるため中盤のみ）
     if board.castle_wk() || board.castle_wq() {
-        score += CASTLING_RIGHTS_BONUS;
+        mg_score += CASTLING_RIGHTS_BONUS;
     }
     if board.castle_bk() || board.castle_bq() {
-        score -= CASTLING_RIGHTS_BONUS;
+        mg_score -= CASTLING_RIGHTS_BONUS;
     }
 
-    score
+    // モビリティ（利かせているマスの数）。展開・中央制圧ができているほど
+    // 利きが広くなるので、駒の活動度の粗い代理指標として使う
+    let white_mobility = board.attacked_by(Color::White).count_ones() as i32;
+    let black_mobility = board.attacked_by(Color::Black).count_ones() as i32;
+    let mobility_score = (white_mobility - black_mobility) * MOBILITY_BONUS;
+    mg_score += mobility_score;
+    eg_score += mobility_score;
+
+    let phase = phase.min(MAX_PHASE);
+    (mg_score * phase + eg_score * (MAX_PHASE - phase)) / MAX_PHASE
 }
 
 /// 駒の基本価値を取得する
-fn get_piece_value(kind: Kind) -> i32 {
+pub(crate) fn get_piece_value(kind: Kind) -> i32 {
     match kind {
         Kind::Pawn => PAWN_VALUE,
         Kind::Knight => KNIGHT_VALUE,
@@ -208,13 +491,15 @@ fn get_piece_value(kind: Kind) -> i32 {
     }
 }
 
-/// 駒の位置評価を取得する
+/// 駒の位置評価を中盤・終盤の両方で取得する
 ///
 /// # 引数
 /// * `piece` - 評価する駒
 /// * `square` - 盤面上の位置（0-63）
-/// * `is_endgame` - 終盤かどうか（駒数16個以下）
-fn get_positional_value(piece: Piece, square: usize, is_endgame: bool) -> i32 {
+///
+/// # 戻り値
+/// `(中盤の位置評価値, 終盤の位置評価値)`
+fn get_positional_values(piece: Piece, square: usize) -> (i32, i32) {
     // 黒の駒の場合は盤面を上下反転
     let index = if piece.color == Color::White {
         square
@@ -223,17 +508,11 @@ fn get_positional_value(piece: Piece, square: usize, is_endgame: bool) -> i32 {
     };
 
     match piece.kind {
-        Kind::Pawn => PAWN_TABLE[index],
-        Kind::Knight => KNIGHT_TABLE[index],
-        Kind::Bishop => BISHOP_TABLE[index],
-        Kind::Rook => ROOK_TABLE[index],
-        Kind::Queen => QUEEN_TABLE[index],
-        Kind::King => {
-            if is_endgame {
-                KING_ENDGAME_TABLE[index]
-            } else {
-                KING_MIDDLEGAME_TABLE[index]
-            }
-        }
+        Kind::Pawn => (PAWN_MG_TABLE[index], PAWN_EG_TABLE[index]),
+        Kind::Knight => (KNIGHT_MG_TABLE[index], KNIGHT_EG_TABLE[index]),
+        Kind::Bishop => (BISHOP_MG_TABLE[index], BISHOP_EG_TABLE[index]),
+        Kind::Rook => (ROOK_MG_TABLE[index], ROOK_EG_TABLE[index]),
+        Kind::Queen => (QUEEN_MG_TABLE[index], QUEEN_EG_TABLE[index]),
+        Kind::King => (KING_MIDDLEGAME_TABLE[index], KING_ENDGAME_TABLE[index]),
     }
 }
@@ -0,0 +1,163 @@
+//! マジックビットボードによるスライディング駒（ビショップ/ルーク/クイーン）の利き計算
+//!
+//! マス毎に「関係ある占有マスク」を求め、マジック定数による乗算シフトで
+//! 占有パターンから事前計算済みテーブルへ一意に（同じ利きになる場合は多対一で）
+//! 引けるようにする。レイを1マスずつ辿る代わりに乗算とシフトとテーブル参照で済むため速い
+
+use crate::board::{sliding_attacks, SplitMix64};
+use std::sync::OnceLock;
+
+const BISHOP_DELTAS: [(isize, isize); 4] = [(1, 1), (1, -1), (-1, 1), (-1, -1)];
+const ROOK_DELTAS: [(isize, isize); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+
+/// 1マスぶんのマジックビットボードエントリ
+struct MagicEntry {
+    mask: u64,   // 関係ある占有マス（盤端を除く）
+    magic: u64,  // マジック定数
+    shift: u32,  // 64 - (マスクの立っているビット数)
+    table: Vec<u64>, // 占有パターンごとの利きビットボード
+}
+
+impl MagicEntry {
+    /// 現在の盤面占有状況から利きビットボードを引く
+    fn attacks(&self, occupied: u64) -> u64 {
+        let relevant = occupied & self.mask;
+        let index = (relevant.wrapping_mul(self.magic) >> self.shift) as usize;
+        self.table[index]
+    }
+}
+
+/// ルークの「関係ある占有マス」を求める（盤の端2マスは利きに影響しないので除く）
+fn rook_relevant_mask(sq: usize) -> u64 {
+    let file = (sq % 8) as isize;
+    let rank = (sq / 8) as isize;
+    let mut mask = 0u64;
+    for f in (file + 1)..7 {
+        mask |= 1u64 << (rank * 8 + f);
+    }
+    for f in (1..file).rev() {
+        mask |= 1u64 << (rank * 8 + f);
+    }
+    for r in (rank + 1)..7 {
+        mask |= 1u64 << (r * 8 + file);
+    }
+    for r in (1..rank).rev() {
+        mask |= 1u64 << (r * 8 + file);
+    }
+    mask
+}
+
+/// ビショップの「関係ある占有マス」を求める（盤の縁は利きに影響しないので除く）
+fn bishop_relevant_mask(sq: usize) -> u64 {
+    let file = (sq % 8) as isize;
+    let rank = (sq / 8) as isize;
+    let mut mask = 0u64;
+    for &(df, dr) in &BISHOP_DELTAS {
+        let mut f = file + df;
+        let mut r = rank + dr;
+        while (1..=6).contains(&f) && (1..=6).contains(&r) {
+            mask |= 1u64 << (r * 8 + f);
+            f += df;
+            r += dr;
+        }
+    }
+    mask
+}
+
+/// マスクの全部分集合を列挙する（Carry-Rippletトリック）
+fn subsets_of(mask: u64) -> Vec<u64> {
+    let mut subsets = Vec::with_capacity(1 << mask.count_ones());
+    let mut subset = 0u64;
+    loop {
+        subsets.push(subset);
+        if subset == mask {
+            break;
+        }
+        subset = subset.wrapping_sub(mask) & mask;
+        if subset == 0 {
+            break;
+        }
+    }
+    subsets
+}
+
+/// 指定マスについて、与えられた占有マスクに対するマジック定数とテーブルを探す
+///
+/// スパースな（ビットの少ない）乱数候補を試し、部分集合ごとの「真の利き」
+/// （`sliding_attacks`によるレイ走査で計算）がテーブル衝突を起こさないものが
+/// 見つかるまで繰り返す
+fn find_magic(sq: usize, mask: u64, deltas: &[(isize, isize)], rng: &mut SplitMix64) -> MagicEntry {
+    let bits = mask.count_ones();
+    let shift = 64 - bits;
+    let subsets = subsets_of(mask);
+    let true_attacks: Vec<u64> = subsets
+        .iter()
+        .map(|&occ| sliding_attacks(sq, occ, deltas))
+        .collect();
+
+    loop {
+        // 3つの乱数をANDすることでビットの少ない(スパースな)候補を作る。
+        // スパースな乗数の方が良いマジック定数になりやすいという経験則による
+        let magic = rng.next() & rng.next() & rng.next();
+
+        let mut table: Vec<Option<u64>> = vec![None; 1usize << bits];
+        let mut ok = true;
+        for (i, &occ) in subsets.iter().enumerate() {
+            let index = (occ.wrapping_mul(magic) >> shift) as usize;
+            match table[index] {
+                None => table[index] = Some(true_attacks[i]),
+                Some(v) if v == true_attacks[i] => {}
+                Some(_) => {
+                    ok = false;
+                    break;
+                }
+            }
+        }
+
+        if ok {
+            let table: Vec<u64> = table.into_iter().map(|o| o.unwrap_or(0)).collect();
+            return MagicEntry {
+                mask,
+                magic,
+                shift,
+                table,
+            };
+        }
+    }
+}
+
+/// 64マスぶんのマジックビットボードテーブル（ビショップ・ルーク）
+struct MagicTables {
+    bishop: Vec<MagicEntry>,
+    rook: Vec<MagicEntry>,
+}
+
+/// マジックビットボードテーブルを取得する（初回呼び出し時に一度だけ全マスぶん探索する）
+fn magic_tables() -> &'static MagicTables {
+    static TABLES: OnceLock<MagicTables> = OnceLock::new();
+    TABLES.get_or_init(|| {
+        let mut rng = SplitMix64::new(0xD1B54A32D192ED03);
+        let bishop = (0..64)
+            .map(|sq| find_magic(sq, bishop_relevant_mask(sq), &BISHOP_DELTAS, &mut rng))
+            .collect();
+        let rook = (0..64)
+            .map(|sq| find_magic(sq, rook_relevant_mask(sq), &ROOK_DELTAS, &mut rng))
+            .collect();
+        MagicTables { bishop, rook }
+    })
+}
+
+/// ビショップの利きビットボードを求める（マジックビットボード使用）
+pub fn bishop_attacks(sq: usize, occupied: u64) -> u64 {
+    magic_tables().bishop[sq].attacks(occupied)
+}
+
+/// ルークの利きビットボードを求める（マジックビットボード使用）
+pub fn rook_attacks(sq: usize, occupied: u64) -> u64 {
+    magic_tables().rook[sq].attacks(occupied)
+}
+
+/// クイーンの利きビットボードを求める（ビショップ+ルークの合併）
+pub fn queen_attacks(sq: usize, occupied: u64) -> u64 {
+    bishop_attacks(sq, occupied) | rook_attacks(sq, occupied)
+}
@@ -1,40 +1,85 @@
 mod board;
 mod cache;
+mod evaluate;
+mod magic;
 mod opening;
 
 use board::Board;
 use cache::Cache;
-use opening::OpeningBook;
+use opening::{OpeningBook, PolyglotBook};
 use clap::Parser;
 use std::io::{self, Read};
+use std::time::Duration;
+
+/// 評価関数の種類
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EvaluatorType {
+    /// Piece-Square Tablesを使う高度な評価
+    Advanced,
+    /// 駒の価値の合計のみを使うクラシックな評価
+    Classic,
+}
 
 /// コマンドライン引数
 #[derive(Parser, Debug)]
 #[command(author, version, about = "チェスAI - 標準入力から棋譜を読み込み、次の最善手を出力する", long_about = None)]
 struct Args {
-    /// 探索深度（大きいほど強いが遅い）
-    #[arg(short, long, default_value_t = 3)]
-    depth: u32,
+    /// 思考時間の上限（秒）。反復深化はこの予算を使い切るまで深度を上げ続ける
+    #[arg(short, long, default_value_t = 5)]
+    timeout: u64,
 
     /// 盤面を表示するだけで最善手を計算しない
     #[arg(short, long)]
     print_only: bool,
+
+    /// 外部のPolyglot形式(.bin)オープニングブックのパス
+    ///
+    /// 指定された場合、手書きの内蔵オープニングブックより優先して使われる
+    #[arg(long)]
+    polyglot_book: Option<String>,
+
+    /// 探索に使うワーカースレッド数。未指定または1なら直列探索
+    ///
+    /// 2以上を指定すると、ルートの合法手をワーカー間で分担するルート分割方式の
+    /// 並列探索になる。置換表は全ワーカーで共有される
+    #[arg(long)]
+    threads: Option<usize>,
+
+    /// Chess960（フィッシャーランダムチェス）の開始局面番号（0..=959）
+    ///
+    /// 指定された場合、標準の初期配置の代わりにこの番号の開始局面
+    /// （[`Board::from_chess960_id`]が使う標準的な番号割り当て）から始める
+    #[arg(long)]
+    chess960_id: Option<u16>,
 }
 
 /// メイン関数
 ///
 /// 標準入力から棋譜を読み込み、AIが次の最善手を計算して出力する
-/// コマンドライン引数で探索深度を指定可能（デフォルト3）
+/// コマンドライン引数で思考時間（秒）を指定可能（デフォルト5秒）。反復深化で
+/// 深度1から順に探索し、指定秒数を使い切った時点で最後に完了した深度の手を返す
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args = Args::parse();
-    let depth = args.depth;
+    let timeout = Duration::from_secs(args.timeout);
+    let evaluator = EvaluatorType::Advanced;
+    evaluate::set_evaluator_type(evaluator);
 
     // 標準入力から棋譜（空白区切りの手）を読み、順次適用
     let mut buf = String::new();
     io::stdin().read_to_string(&mut buf)?;
+
+    // 先頭トークンがUCIプロトコルのコマンドなら、そちらのモードとして扱う
+    let first_token = buf.split_whitespace().next().unwrap_or("");
+    if matches!(first_token, "position" | "go" | "uci" | "isready" | "ucinewgame") {
+        return run_uci(&buf, &args);
+    }
+
     let tokens: Vec<String> = buf.split_whitespace().map(|s| s.to_string()).collect();
 
-    let mut board = Board::new();
+    let mut board = match args.chess960_id {
+        Some(id) => Board::from_chess960_id(id),
+        None => Board::new(),
+    };
 
     for (ply, tok) in tokens.iter().enumerate() {
         if tok.ends_with('.') {
@@ -59,30 +104,44 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         return Ok(());
     }
 
-    // オープニングブックを初期化
+    // 外部のPolyglotブックが指定されていれば優先して検索する
+    let polyglot_move = args.polyglot_book.as_ref().and_then(|path| {
+        match PolyglotBook::load(path) {
+            Ok(book) => book.lookup(&board),
+            Err(e) => {
+                eprintln!("; Warning: Failed to load polyglot book '{}': {}", path, e);
+                None
+            }
+        }
+    });
+
+    // 手書きのオープニングブックを初期化
     let opening_book = OpeningBook::new();
 
-    // オープニングブックから手を検索（現在の盤面を渡す）
-    let san = if let Some(opening_move) = opening_book.lookup(&board) {
+    // Polyglotブック → 内蔵オープニングブックの順に手を検索
+    let san = if let Some(book_move) = polyglot_move {
+        eprintln!("; Using polyglot book");
+        book_move
+    } else if let Some(opening_move) = opening_book.lookup(&board) {
         eprintln!("; Using opening book");
         opening_move
     } else {
         // キャッシュを初期化
         let cache = Cache::new();
 
-        // 盤面をシリアライズしてキャッシュキーを生成
-        let board_state = board.serialize();
+        // 盤面のZobristハッシュをキャッシュキーとして使う
+        let board_hash = board.zobrist();
 
         // キャッシュから結果を読み込む
-        if let Some(cached_move) = cache.read(&board_state, depth) {
+        if let Some(cached_move) = cache.read(board_hash, args.timeout, args.threads, evaluator) {
             eprintln!("; Using cached result");
             cached_move
         } else {
-            // AIが次の一手を考える
-            if let Some(best_move) = board.find_best_move(depth) {
+            // AIが次の一手を考える（反復深化、時間切れまで深度を上げ続ける）
+            if let Some(best_move) = board.find_best_move(timeout, args.threads) {
                 let san = board.move_to_san(best_move);
                 // キャッシュに保存
-                if let Err(e) = cache.write(&board_state, depth, &san) {
+                if let Err(e) = cache.write(board_hash, args.timeout, args.threads, evaluator, &san) {
                     eprintln!("; Warning: Failed to write cache: {}", e);
                 }
                 san
@@ -104,3 +163,98 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+/// UCIプロトコルの最小限のサブセットを処理する
+///
+/// 標準入力を1行ずつコマンドとして読み、`position`で盤面を構築し、`go`に対して
+/// `bestmove <手>`（長手記法）を標準出力に返す。`uci`/`isready`には最低限のハンドシェイクで
+/// 応答し、標準的なUCI GUIや自動テストハーネスから直接駆動できるようにする
+///
+/// # 引数
+/// * `input` - 標準入力から読み込んだ全テキスト
+/// * `args` - コマンドライン引数（`go`に深度・時間指定が無い場合のデフォルトに使う）
+fn run_uci(input: &str, args: &Args) -> Result<(), Box<dyn std::error::Error>> {
+    let mut board = Board::new();
+
+    for line in input.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let mut it = line.split_whitespace();
+        match it.next().unwrap_or("") {
+            "uci" => {
+                println!("id name greedy-chess");
+                println!("id author cympfh");
+                println!("uciok");
+            }
+            "isready" => println!("readyok"),
+            "ucinewgame" => board = Board::new(),
+            "quit" => return Ok(()),
+            "position" => {
+                board = parse_position_command(&mut it)?;
+            }
+            "go" => {
+                let mut depth: Option<u32> = None;
+                let mut movetime: Option<u64> = None;
+                while let Some(tok) = it.next() {
+                    match tok {
+                        "depth" => depth = it.next().and_then(|s| s.parse().ok()),
+                        "movetime" => movetime = it.next().and_then(|s| s.parse().ok()),
+                        _ => {}
+                    }
+                }
+
+                let best_move = if let Some(d) = depth {
+                    board.search_fixed_depth(d, Duration::from_secs(60))
+                } else if let Some(ms) = movetime {
+                    board.find_best_move(Duration::from_millis(ms), args.threads)
+                } else {
+                    board.find_best_move(Duration::from_secs(args.timeout), args.threads)
+                };
+
+                match best_move {
+                    Some(mv) => println!("bestmove {}", board.move_to_uci(mv)),
+                    None => println!("bestmove 0000"),
+                }
+            }
+            _ => {} // 未対応のコマンドは無視
+        }
+    }
+
+    Ok(())
+}
+
+/// `position startpos [moves ...]` / `position fen <FEN> [moves ...]` /
+/// `position chess960 <id> [moves ...]` を解釈して盤面を構築する
+fn parse_position_command<'a>(
+    it: &mut std::str::SplitWhitespace<'a>,
+) -> Result<Board, Box<dyn std::error::Error>> {
+    let mut board = match it.next() {
+        Some("startpos") => Board::new(),
+        Some("fen") => {
+            let fen_tokens: Vec<&str> = it.clone().take_while(|&t| t != "moves").collect();
+            for _ in 0..fen_tokens.len() {
+                it.next();
+            }
+            Board::from_fen(&fen_tokens.join(" "))?
+        }
+        Some("chess960") => {
+            let id: u16 = it
+                .next()
+                .ok_or("missing chess960 id")?
+                .parse()
+                .map_err(|_| "invalid chess960 id")?;
+            Board::from_chess960_id(id)
+        }
+        _ => Board::new(),
+    };
+
+    if it.next() == Some("moves") {
+        for mv in it {
+            board.parse_and_play_token(mv)?;
+        }
+    }
+
+    Ok(board)
+}
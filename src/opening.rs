@@ -1,16 +1,19 @@
-use crate::board::Board;
+use crate::board::{Board, Color, Kind, SplitMix64};
 use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::sync::OnceLock;
 
 /// オープニングブック
-/// 各局面（盤面のシリアライズ文字列）に対して推奨される次の手を保持
+/// 各局面（盤面のZobristハッシュ）に対して推奨される次の手を保持
 pub struct OpeningBook {
-    book: HashMap<String, Vec<String>>,
+    book: HashMap<u64, Vec<String>>,
 }
 
 impl OpeningBook {
     /// オープニングブックを初期化
     ///
-    /// 各オープニングを実際に盤面に適用して、serialize() した値をキーとする
+    /// 各オープニングを実際に盤面に適用して、zobrist() の値をキーとする
     pub fn new() -> Self {
         let mut book = HashMap::new();
 
@@ -57,8 +60,8 @@ impl OpeningBook {
                     }
                 }
 
-                // 盤面をシリアライズしてキーとする
-                let key = board.serialize();
+                // 盤面のZobristハッシュをキーとする
+                let key = board.zobrist();
                 let recommended = line[n];
 
                 // 既存のエントリに追加、または新規作成
@@ -79,13 +82,186 @@ impl OpeningBook {
     /// # Returns
     /// オープニングブックに登録されている推奨手（複数候補がある場合は最初の手を返す）
     pub fn lookup(&self, board: &Board) -> Option<String> {
-        let key = board.serialize();
+        let key = board.zobrist();
         self.book
             .get(&key)
             .and_then(|candidates| candidates.first().cloned())
     }
 }
 
+/// Polyglot (.bin) 形式のオープニングブック1エントリ（16バイト、ビッグエンディアン）
+struct PolyglotEntry {
+    key: u64,
+    mv: u16,
+    weight: u16,
+    #[allow(dead_code)]
+    learn: u32,
+}
+
+/// Polyglotブック形式のZobristキーに使う乱数定数表（駒×色×マス768個、
+/// キャスリング権4個、アンパッサンのファイル8個、手番1個の計781個）
+///
+/// [`Board::zobrist`]（本クレート内部の置換表・オープニングブック用）とは
+/// 完全に別系統の定数で、[`polyglot_key`]からのみ使われる
+struct PolyglotRandom {
+    pieces: [[u64; 64]; 12],
+    castle: [u64; 4],
+    ep_file: [u64; 8],
+    turn: u64,
+}
+
+/// Polyglot乱数定数表を取得する（初回呼び出し時に一度だけ初期化）
+///
+/// 注意: 値そのものは本クレート独自にSplitMix64で決定的生成した定数であり、
+/// 公式のPolyglotランダム定数表（`random.h`のRandom64）の値ではない。構成（駒の
+/// 並び順・マスの添字・キャスリング/アンパッサン/手番の扱い）はPolyglotの形式に
+/// 合わせてある
+fn polyglot_random() -> &'static PolyglotRandom {
+    static TABLE: OnceLock<PolyglotRandom> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let mut rng = SplitMix64::new(0x3243F6A8885A308D);
+        PolyglotRandom {
+            pieces: std::array::from_fn(|_| std::array::from_fn(|_| rng.next())),
+            castle: std::array::from_fn(|_| rng.next()),
+            ep_file: std::array::from_fn(|_| rng.next()),
+            turn: rng.next(),
+        }
+    })
+}
+
+/// Polyglotの駒符号化（駒種×2 + 色。白=1, 黒=0）に対応する添字を返す
+fn polyglot_piece_index(kind: Kind, color: Color) -> usize {
+    let kind_rank = match kind {
+        Kind::Pawn => 0,
+        Kind::Knight => 1,
+        Kind::Bishop => 2,
+        Kind::Rook => 3,
+        Kind::Queen => 4,
+        Kind::King => 5,
+    };
+    kind_rank * 2 + if color == Color::White { 1 } else { 0 }
+}
+
+/// Polyglot形式の盤面Zobristキーを計算する
+///
+/// [`Board::zobrist`]とは別の乱数定数表（[`polyglot_random`]）を使い、駒配置・
+/// キャスリング権・アンパッサン・手番をPolyglot仕様の並び順でXOR結合する。
+/// [`PolyglotBook::lookup`]専用で、本クレート内部の置換表やオープニングブックの
+/// キー（`Board::zobrist`）には一切影響しない
+fn polyglot_key(board: &Board) -> u64 {
+    let table = polyglot_random();
+    let mut h = 0u64;
+
+    for sq in 0..64 {
+        if let Some(p) = board.piece_at(sq) {
+            h ^= table.pieces[polyglot_piece_index(p.kind, p.color)][sq];
+        }
+    }
+    if board.castle_wk() {
+        h ^= table.castle[0];
+    }
+    if board.castle_wq() {
+        h ^= table.castle[1];
+    }
+    if board.castle_bk() {
+        h ^= table.castle[2];
+    }
+    if board.castle_bq() {
+        h ^= table.castle[3];
+    }
+    if let Some(file) = board.ep_capture_file() {
+        h ^= table.ep_file[file];
+    }
+    if board.side() == Color::White {
+        h ^= table.turn;
+    }
+
+    h
+}
+
+/// 外部のPolyglot形式オープニングブックを保持する
+///
+/// `.bin`ファイルの読み込みとキーの二分探索はPolyglot形式どおりに行うが、局面キーは
+/// [`polyglot_random`]の独自定数で計算する。そのため公式のRandom64定数表で作られた
+/// 既存のコミュニティ製ブックのキーとは一致せず、そうしたブックからは手が見つからない
+pub struct PolyglotBook {
+    entries: Vec<PolyglotEntry>,
+}
+
+impl PolyglotBook {
+    /// `.bin`ファイルを読み込む
+    ///
+    /// 16バイトずつ `(key: u64, move: u16, weight: u16, learn: u32)` の配列で、
+    /// キー昇順にソートされている前提（Polyglot形式の仕様どおり）
+    pub fn load(path: &str) -> io::Result<Self> {
+        let data = fs::read(path)?;
+        if data.len() % 16 != 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Polyglot book size is not a multiple of 16 bytes",
+            ));
+        }
+
+        let mut entries = Vec::with_capacity(data.len() / 16);
+        for chunk in data.chunks_exact(16) {
+            let key = u64::from_be_bytes(chunk[0..8].try_into().unwrap());
+            let mv = u16::from_be_bytes(chunk[8..10].try_into().unwrap());
+            let weight = u16::from_be_bytes(chunk[10..12].try_into().unwrap());
+            let learn = u32::from_be_bytes(chunk[12..16].try_into().unwrap());
+            entries.push(PolyglotEntry {
+                key,
+                mv,
+                weight,
+                learn,
+            });
+        }
+
+        Ok(PolyglotBook { entries })
+    }
+
+    /// 指定キーに一致するエントリの範囲を二分探索で求める（ソート済み前提）
+    fn matching_range(&self, key: u64) -> &[PolyglotEntry] {
+        let start = self.entries.partition_point(|e| e.key < key);
+        let end = self.entries.partition_point(|e| e.key <= key);
+        &self.entries[start..end]
+    }
+
+    /// 現在の盤面に対する推奨手をUCI形式（例: "e2e4", "e7e8q"）で返す
+    ///
+    /// 同一局面に複数の候補がある場合は重み（weight）が最大のものを選ぶ
+    pub fn lookup(&self, board: &Board) -> Option<String> {
+        let key = polyglot_key(board);
+        let candidates = self.matching_range(key);
+        let best = candidates.iter().max_by_key(|e| e.weight)?;
+        Some(decode_polyglot_move(best.mv))
+    }
+}
+
+/// Polyglotのパック済み指し手（16bit）をUCI形式の文字列にデコードする
+///
+/// bit 0-5: 移動先, bit 6-11: 移動元, bit 12-14: 昇格する駒種
+fn decode_polyglot_move(mv: u16) -> String {
+    let to_sq = (mv & 0x3f) as usize;
+    let from_sq = ((mv >> 6) & 0x3f) as usize;
+    let promo = (mv >> 12) & 0x7;
+
+    let square_to_str = |sq: usize| {
+        let file = (b'a' + (sq % 8) as u8) as char;
+        let rank = (b'1' + (sq / 8) as u8) as char;
+        format!("{}{}", file, rank)
+    };
+
+    let mut s = format!("{}{}", square_to_str(from_sq), square_to_str(to_sq));
+    match promo {
+        1 => s.push('n'),
+        2 => s.push('b'),
+        3 => s.push('r'),
+        4 => s.push('q'),
+        _ => {}
+    }
+    s
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -130,4 +306,36 @@ mod tests {
         }
         assert_eq!(book.lookup(&board), None);
     }
+
+    /// `polyglot_key`は`Board::zobrist`とは別の定数表を使う、決定的なキーであることを確認する
+    #[test]
+    fn polyglot_key_differs_from_internal_zobrist() {
+        let board = Board::new();
+        assert_ne!(polyglot_key(&board), board.zobrist());
+
+        // 同一局面なら毎回同じキーになる（決定的）
+        assert_eq!(polyglot_key(&board), polyglot_key(&Board::new()));
+    }
+
+    /// `PolyglotBook::lookup`が`polyglot_key`のキーで`.bin`ファイルを検索することを確認する
+    #[test]
+    fn polyglot_book_lookup_uses_polyglot_key() {
+        let board = Board::new();
+        let key = polyglot_key(&board);
+
+        // 16バイトのPolyglotエントリを1件だけ持つ.binファイルを組み立てる
+        let mut data = Vec::with_capacity(16);
+        data.extend_from_slice(&key.to_be_bytes());
+        data.extend_from_slice(&0x031cu16.to_be_bytes()); // e2e4 (from=e2=12, to=e4=28)
+        data.extend_from_slice(&1u16.to_be_bytes()); // weight
+        data.extend_from_slice(&0u32.to_be_bytes()); // learn
+
+        let path = std::env::temp_dir().join("greedy_chess_test_polyglot_book_lookup.bin");
+        fs::write(&path, &data).unwrap();
+
+        let book = PolyglotBook::load(path.to_str().unwrap()).unwrap();
+        assert_eq!(book.lookup(&board), Some("e2e4".to_string()));
+
+        fs::remove_file(&path).unwrap();
+    }
 }